@@ -0,0 +1,311 @@
+use crate::ipc::protocol::Event;
+use crate::layout::{Id, LayoutType};
+use crate::utils::Edge;
+use crate::window::WindowState;
+use crate::workspace::WorkspaceTarget;
+use crate::PocoWM;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// Default percentage of `grow` and `shrink`
+const RESIZE_STEP: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn edge(self) -> Edge {
+        match self {
+            Self::Left => Edge::LEFT,
+            Self::Right => Edge::RIGHT,
+            Self::Up => Edge::TOP,
+            Self::Down => Edge::BOTTOM,
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            _ => Err(anyhow!("Unknown direction: {}", s)),
+        }
+    }
+}
+
+/// Everything the compositor can be asked to do, from a key binding or elsewhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// `spawn <command>`, run through `sh -c`
+    Spawn(String),
+    /// `focus left|right|up|down`, crosses to the next output at the edge of the layout
+    Focus(Direction),
+    /// `layout horizontal|vertical|tabbed|stacked`, changes the focused window's container
+    Layout(LayoutType),
+    /// `layout toggle`, switches the focused window's container between horizontal and vertical
+    ToggleLayout,
+    /// `split horizontal|vertical`, wraps the focused window in a new container
+    Split(LayoutType),
+    /// `floating toggle`
+    ToggleFloating,
+    /// `maximize toggle`
+    ToggleMaximize,
+    /// `fullscreen toggle`
+    ToggleFullscreen,
+    /// `decorations toggle`, shows or hides the title bar of the focused window
+    ToggleDecorations,
+    /// `grow [percent]` or `shrink [percent]`, changes the share of its container the focused
+    /// window takes, 10% by default
+    Resize(i32),
+    /// `equalize`, gives every window of the focused window's container the same share
+    Equalize,
+    /// `minimize`
+    Minimize,
+    /// `unminimize [app_id]`, restores the most recently minimized window, of `app_id` if given
+    Unminimize(Option<String>),
+    /// `minimized`, shows the minimized windows to pick the one to restore with the keyboard
+    PickMinimized,
+    /// `close`
+    Close,
+    /// `kill`, disconnects the client of the focused window
+    Kill,
+    /// `workspace <name>|next|prev`
+    Workspace(WorkspaceTarget),
+    /// `move workspace <name>|next|prev`, moves the focused window there
+    MoveToWorkspace(WorkspaceTarget),
+    /// `move output left|right|up|down`, moves the focused window to the next output
+    MoveToOutput(Direction),
+    /// `theme <name>`, switches to one of the `[themes]` of the config file, or back to its
+    /// `[theme]` with `theme default`
+    Theme(String),
+    /// `reload`, reloads the config file
+    ReloadConfig,
+    /// `quit`
+    Quit,
+}
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (command, args) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let args = args.trim();
+        let action = match (command, args) {
+            ("spawn", "") => return Err(anyhow!("spawn needs a command")),
+            ("spawn", command) => Self::Spawn(command.into()),
+            ("focus", direction) => Self::Focus(direction.parse()?),
+            ("layout", "toggle") => Self::ToggleLayout,
+            ("layout", layout_type) => Self::Layout(layout_type.parse()?),
+            ("split", layout_type) => match layout_type.parse()? {
+                layout_type @ (LayoutType::Horizontal | LayoutType::Vertical) => {
+                    Self::Split(layout_type)
+                }
+                _ => return Err(anyhow!("Can only split horizontally or vertically")),
+            },
+            ("floating", "toggle" | "") => Self::ToggleFloating,
+            ("maximize", "toggle" | "") => Self::ToggleMaximize,
+            ("fullscreen", "toggle" | "") => Self::ToggleFullscreen,
+            ("decorations", "toggle" | "") => Self::ToggleDecorations,
+            ("grow", "") => Self::Resize(RESIZE_STEP),
+            ("grow", percent) => Self::Resize(parse_percent(percent)?),
+            ("shrink", "") => Self::Resize(-RESIZE_STEP),
+            ("shrink", percent) => Self::Resize(-parse_percent(percent)?),
+            ("equalize", "") => Self::Equalize,
+            ("minimize", "") => Self::Minimize,
+            ("unminimize", "") => Self::Unminimize(None),
+            ("unminimize", app_id) => Self::Unminimize(Some(app_id.into())),
+            ("minimized", "") => Self::PickMinimized,
+            ("close", "") => Self::Close,
+            ("kill", "") => Self::Kill,
+            ("workspace", target) => Self::Workspace(target.parse()?),
+            ("move", args) => match args.split_once(char::is_whitespace) {
+                Some(("workspace", target)) => Self::MoveToWorkspace(target.trim().parse()?),
+                Some(("output", direction)) => Self::MoveToOutput(direction.trim().parse()?),
+                _ => return Err(anyhow!("Unknown command: {}", s)),
+            },
+            ("theme", "") => return Err(anyhow!("theme needs a name")),
+            ("theme", name) => Self::Theme(name.into()),
+            ("reload", "") => Self::ReloadConfig,
+            ("quit", "") => Self::Quit,
+            _ => return Err(anyhow!("Unknown command: {}", s)),
+        };
+        Ok(action)
+    }
+}
+
+fn parse_percent(s: &str) -> Result<i32> {
+    s.trim_end_matches('%')
+        .parse()
+        .ok()
+        .filter(|percent| (1..100).contains(percent))
+        .ok_or_else(|| anyhow!("Invalid percentage: {}", s))
+}
+
+impl<'de> Deserialize<'de> for Action {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl PocoWM {
+    pub fn run_action(&mut self, action: Action) {
+        let focused_window = self.focused_window();
+        match action {
+            Action::Spawn(command) => {
+                let _ = std::process::Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .spawn();
+            }
+            Action::Focus(direction) => {
+                let neighbor = focused_window
+                    .and_then(|w| self.layout.get_window_id(&w))
+                    .and_then(|id| self.layout.get_window_neighbor(id, direction.edge()));
+                match neighbor {
+                    Some(id) => self.focus_window(Some(id)),
+                    None => {
+                        self.output_in_direction(direction)
+                            .map(|output| self.focus_output(&output));
+                    }
+                }
+            }
+            Action::Layout(layout_type) => {
+                self.set_layout_type(|_| layout_type);
+            }
+            Action::ToggleLayout => {
+                self.set_layout_type(|layout_type| match layout_type {
+                    LayoutType::Horizontal => LayoutType::Vertical,
+                    LayoutType::Vertical => LayoutType::Horizontal,
+                    LayoutType::Tabbed | LayoutType::Stacked => LayoutType::Horizontal,
+                });
+            }
+            Action::Split(layout_type) => {
+                self.switch_to_layout(layout_type);
+            }
+            Action::ToggleFloating => self.toggle_floating(),
+            Action::ToggleMaximize => {
+                let Some(xdg) = focused_window.as_ref().and_then(|w| w.toplevel()) else {
+                    return;
+                };
+                let xdg = xdg.clone();
+                if focused_window.is_some_and(|w| w.state().contains(WindowState::MAXIMIZED)) {
+                    self.xdg_unmaximize_request(&xdg);
+                } else {
+                    self.xdg_maximize_request(&xdg);
+                }
+            }
+            Action::ToggleFullscreen => {
+                let Some(xdg) = focused_window.as_ref().and_then(|w| w.toplevel()) else {
+                    return;
+                };
+                let xdg = xdg.clone();
+                if focused_window.is_some_and(|w| w.state().contains(WindowState::FULLSCREEN)) {
+                    self.xdg_unfullscreen_request(&xdg);
+                } else {
+                    self.xdg_fullscreen_request(&xdg, None);
+                }
+            }
+            Action::ToggleDecorations => {
+                let Some(window) = focused_window else {
+                    return;
+                };
+                window.toggle_decorations();
+                self.renderer.render(&self.layout);
+            }
+            Action::Resize(percent) => {
+                self.split_child()
+                    .map(|id| self.layout.resize_element(id, percent as f64 / 100.0));
+                self.renderer.render(&self.layout);
+            }
+            Action::Equalize => {
+                self.split_child()
+                    .and_then(|id| self.layout.get_parent(id))
+                    .map(|id| self.layout.equalize(id));
+                self.renderer.render(&self.layout);
+            }
+            Action::Minimize => {
+                if let Some(xdg) = focused_window.as_ref().and_then(|w| w.toplevel()) {
+                    self.xdg_minimize_request(xdg);
+                }
+            }
+            Action::Unminimize(app_id) => {
+                self.last_minimized(app_id.as_deref())
+                    .map(|window| self.unminimize_window(&window));
+            }
+            Action::PickMinimized => self.open_picker(),
+            Action::Close => {
+                focused_window
+                    .as_ref()
+                    .and_then(|w| w.toplevel())
+                    .map(|t| t.send_close());
+            }
+            Action::Kill => {
+                focused_window
+                    .as_ref()
+                    .and_then(|w| w.toplevel())
+                    .map(|t| self.kill_client(t.wl_surface()));
+            }
+            Action::Workspace(target) => self.switch_workspace(&target),
+            Action::MoveToWorkspace(target) => self.move_to_workspace(&target),
+            Action::MoveToOutput(direction) => self.move_to_output(direction),
+            Action::Theme(name) => self.set_theme(&name),
+            Action::ReloadConfig => {
+                if let Err(err) = self.reload_config() {
+                    eprintln!("{:?}\nKeeping the previous config", err);
+                }
+            }
+            Action::Quit => self.loop_signal.stop(),
+        }
+    }
+
+    /// Element containing the focused tiled window, in the closest container split along an axis
+    fn split_child(&self) -> Option<Id> {
+        self.focused_window()
+            .filter(|w| w.state().is_empty())
+            .and_then(|w| self.layout.get_window_id(&w))
+            .and_then(|id| {
+                self.layout.get_split_child(id, |layout_type| {
+                    matches!(layout_type, LayoutType::Horizontal | LayoutType::Vertical)
+                })
+            })
+    }
+
+    /// Changes the layout type of the focused tiled window's container
+    fn set_layout_type(&mut self, f: impl FnOnce(LayoutType) -> LayoutType) {
+        let changed = self
+            .focused_window()
+            .and_then(|w| if w.state().is_empty() { Some(w) } else { None })
+            .and_then(|w| self.layout.get_window_id(&w))
+            .and_then(|id| self.layout.get_parent(id))
+            .and_then(|id| self.layout.get_sublayout_mut(id))
+            .and_then(|layout| {
+                let layout_type = f(layout.layout_type);
+                (layout_type != layout.layout_type).then(|| {
+                    layout.layout_type = layout_type;
+                    (layout.id, layout_type)
+                })
+            });
+        self.renderer.render(&self.layout);
+        changed.map(|(id, layout_type)| {
+            self.emit_ipc_event(Event::LayoutType {
+                id: id.into(),
+                layout_type,
+            })
+        });
+    }
+}
@@ -0,0 +1,143 @@
+use crate::config::OutputMode;
+use crate::ipc::protocol::Event;
+use crate::PocoWM;
+use anyhow::{anyhow, Context as _, Result};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::damage::OutputDamageTracker;
+use smithay::backend::renderer::pixman::{PixmanRenderBuffer, PixmanRenderer};
+use smithay::backend::renderer::{Bind as _, Offscreen as _};
+use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::utils::{Buffer, Logical, Physical, Point, Size, Transform};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessOptions {
+    pub size: Size<i32, Physical>,
+    /// Refresh rate in mHz, like [`Mode::refresh`]
+    pub refresh: i32,
+    /// Location of the output in the global space
+    pub position: Point<i32, Logical>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            size: (1920, 1080).into(),
+            refresh: 60_000,
+            position: (0, 0).into(),
+        }
+    }
+}
+
+impl HeadlessOptions {
+    /// Parses a `WIDTHxHEIGHT[@HZ]` string, e.g. `1280x720@30`
+    pub fn parse(s: &str) -> Result<Self> {
+        let mode: OutputMode = s.parse()?;
+        Ok(Self {
+            size: mode.size,
+            refresh: mode.refresh.unwrap_or(Self::default().refresh),
+            ..Self::default()
+        })
+    }
+}
+
+/// Creates the buffer the renderer draws into, it has to be recreated when the mode changes
+fn bind_buffer(renderer: &mut PixmanRenderer, size: Size<i32, Physical>) -> Result<()> {
+    let buffer: PixmanRenderBuffer = renderer
+        .create_buffer(
+            Fourcc::Argb8888,
+            Size::<i32, Buffer>::from((size.w, size.h)),
+        )
+        .map_err(|err| anyhow!("{:#?}", err))
+        .context("Failed to create headless buffer")?;
+    // The renderer keeps its target bound, there is nothing to present
+    renderer
+        .bind(buffer)
+        .map_err(|err| anyhow!("{:#?}", err))
+        .context("Failed to bind headless buffer")?;
+    Ok(())
+}
+
+impl PocoWM {
+    /// Creates a virtual output rendered offscreen with pixman, so the compositor can run
+    /// without any display. Can be called again to add more outputs
+    pub fn init_headless(&mut self, options: HeadlessOptions) -> Result<Output> {
+        let mut renderer = PixmanRenderer::new()
+            .map_err(|err| anyhow!("{:#?}", err))
+            .context("Failed to init pixman renderer")?;
+        bind_buffer(&mut renderer, options.size)?;
+
+        let mode = Mode {
+            size: options.size,
+            refresh: options.refresh,
+        };
+
+        let name = match self.renderer.outputs().count() {
+            0 => "headless".into(),
+            n => format!("headless-{}", n + 1),
+        };
+        let output = Output::new(
+            name,
+            PhysicalProperties {
+                size: (0, 0).into(),
+                subpixel: Subpixel::Unknown,
+                make: "PocoWM".into(),
+                model: "Headless".into(),
+            },
+        );
+        output.create_global::<PocoWM>(&self.display);
+        output.change_current_state(
+            Some(mode),
+            Some(Transform::Normal),
+            None,
+            Some(options.position),
+        );
+        output.set_preferred(mode);
+        self.renderer.map_output(&output, options.position);
+        self.configure_output(&output);
+        self.add_output(&output);
+        self.emit_ipc_event(Event::OutputAdded(self.output_info(&output)));
+        self.add_output_head(&output);
+
+        let mut damage_tracker = OutputDamageTracker::from_output(&output);
+        let mut buffer_size = options.size;
+
+        let timer_output = output.clone();
+        self.event_loop
+            .borrow_mut()
+            .handle()
+            .insert_source(Timer::immediate(), move |_, _, state| {
+                let output = &timer_output;
+                let result = (|| {
+                    let size = output.current_mode().map(|mode| mode.size);
+                    if let Some(size) = size.filter(|size| *size != buffer_size) {
+                        bind_buffer(&mut renderer, size)?;
+                        buffer_size = size;
+                    }
+                    let elements = state.renderer.output_elements(&mut renderer, output);
+                    damage_tracker
+                        .render_output(&mut renderer, 0, &elements, [0.8, 0.8, 0.8, 1.0])
+                        .context("Failed to render output")?;
+
+                    state.send_frames(output);
+
+                    state.renderer.refresh();
+                    state.popups.cleanup();
+                    let _ = state.display.flush_clients();
+                    Ok::<_, anyhow::Error>(())
+                })();
+                if let Err(err) = result {
+                    eprintln!("{:?}", err);
+                }
+                let refresh = output
+                    .current_mode()
+                    .map_or(options.refresh, |mode| mode.refresh);
+                TimeoutAction::ToDuration(Duration::from_micros(1_000_000_000 / refresh as u64))
+            })
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("Failed to insert headless timer")?;
+
+        Ok(output)
+    }
+}
@@ -1 +1,2 @@
+pub mod headless;
 pub mod winit;
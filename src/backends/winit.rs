@@ -1,47 +1,175 @@
+use crate::ipc::protocol::Event;
+use crate::output::{FixedMode, OutputSettings};
+use crate::renderer::OutputElement;
 use crate::PocoWM;
 use anyhow::{anyhow, Context as _, Result};
 use smithay::backend::renderer::damage::OutputDamageTracker;
-use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
+use smithay::backend::renderer::element::utils::{
+    CropRenderElement, Relocate, RelocateRenderElement,
+};
+use smithay::backend::renderer::element::{Element as _, RenderElement as _};
 use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::backend::renderer::{Frame as _, Renderer as _};
 use smithay::backend::winit::{self, WinitEvent};
-use smithay::desktop::space::render_output;
 use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
-use smithay::utils::{Rectangle, Transform};
-use std::time::Duration;
+use smithay::utils::{Physical, Point, Rectangle, Size, Transform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WinitOptions {
+    /// Number of outputs, placed side by side in the window
+    pub outputs: usize,
+}
+
+impl Default for WinitOptions {
+    fn default() -> Self {
+        Self { outputs: 1 }
+    }
+}
+
+impl WinitOptions {
+    /// Parses the number of outputs, e.g. `2`
+    pub fn parse(s: &str) -> Result<Self> {
+        let outputs = s
+            .parse()
+            .ok()
+            .filter(|outputs| *outputs > 0)
+            .ok_or_else(|| anyhow!("Invalid number of outputs: {}", s))?;
+        Ok(Self { outputs })
+    }
+}
+
+type WinitElement = CropRenderElement<RelocateRenderElement<OutputElement<GlesRenderer>>>;
+
+/// Clear color of the window, behind the outputs
+const CLEAR_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+
+/// Mode and location in the window of the output `i` out of `count` sharing a window of `size`
+fn output_area(size: Size<i32, Physical>, i: usize, count: usize) -> (Mode, Point<i32, Physical>) {
+    let width = size.w / count as i32;
+    let mode = Mode {
+        size: (width, size.h).into(),
+        refresh: 60_000,
+    };
+    (mode, (width * i as i32, 0).into())
+}
+
+/// Draws the parts of `elements`, front to back, under `damage`. The window is rendered upside
+/// down by GL
+fn draw_damage(
+    renderer: &mut GlesRenderer,
+    size: Size<i32, Physical>,
+    elements: &[WinitElement],
+    damage: &[Rectangle<i32, Physical>],
+) -> Result<()> {
+    let mut frame = renderer
+        .render(size, Transform::Flipped180)
+        .map_err(|err| anyhow!("{:#?}", err))
+        .context("Failed to render outputs")?;
+    frame
+        .clear(CLEAR_COLOR.into(), damage)
+        .map_err(|err| anyhow!("{:#?}", err))
+        .context("Failed to clear outputs")?;
+    for element in elements.iter().rev() {
+        let dst = element.geometry(1.0.into());
+        let element_damage = damage
+            .iter()
+            .filter_map(|rect| rect.intersection(dst))
+            .map(|rect| Rectangle::new(rect.loc - dst.loc, rect.size))
+            .collect::<Vec<_>>();
+        if element_damage.is_empty() {
+            continue;
+        }
+        element
+            .draw(&mut frame, element.src(), dst, &element_damage, &[])
+            .map_err(|err| anyhow!("{:#?}", err))
+            .context("Failed to draw element")?;
+    }
+    // The frame is submitted right after, nothing has to wait for it to be done
+    let _ = frame
+        .finish()
+        .map_err(|err| anyhow!("{:#?}", err))
+        .context("Failed to finish frame")?;
+    Ok(())
+}
 
 impl PocoWM {
-    pub fn init_winit(&mut self) -> Result<()> {
+    /// Gives the outputs their share of a window of `size`, placing the enabled ones side by
+    /// side unless the config puts them elsewhere
+    fn layout_winit_outputs(&mut self, outputs: &[Output], size: Size<i32, Physical>) {
+        let mut x = 0;
+        for (i, output) in outputs.iter().enumerate() {
+            let (mode, _) = output_area(size, i, outputs.len());
+            // Only the size of the window is available
+            output.current_mode().map(|mode| output.delete_mode(mode));
+            let settings = OutputSettings {
+                mode: Some(mode),
+                position: Some((x, 0).into()),
+                ..Default::default()
+            };
+            self.apply_output_settings(output, settings);
+            self.configure_output(output);
+            x += self
+                .renderer
+                .output_geometry(output)
+                .map_or(0, |rect| rect.size.w);
+        }
+    }
+}
+
+impl PocoWM {
+    /// Opens a nested window split into `options.outputs` outputs, side by side. Winit only
+    /// allows one event loop per process, so the outputs share one window instead of having
+    /// their own, each tracking the damage of its area of the window
+    pub fn init_winit(&mut self, options: WinitOptions) -> Result<()> {
         let (mut backend, winit) = winit::init::<GlesRenderer>()
             .map_err(|err| anyhow!("{:#?}", err))
             .context("Failed to init winit")?;
+        // The cursor is drawn with the outputs
+        backend.window().set_cursor_visible(false);
 
-        let mode = Mode {
-            size: backend.window_size(),
-            refresh: 60_000,
-        };
+        let count = options.outputs;
+        let outputs = (0..count)
+            .map(|i| {
+                let name = match i {
+                    0 => "winit".into(),
+                    i => format!("winit-{}", i + 1),
+                };
+                let output = Output::new(
+                    name,
+                    PhysicalProperties {
+                        size: (0, 0).into(),
+                        subpixel: Subpixel::Unknown,
+                        make: "PocoWM".into(),
+                        model: "Winit".into(),
+                    },
+                );
+                output.user_data().insert_if_missing(|| FixedMode);
+                output.create_global::<PocoWM>(&self.display);
+                output.change_current_state(None, Some(Transform::Normal), None, None);
+                self.renderer.map_output(&output, (0, 0));
+                output
+            })
+            .collect::<Vec<_>>();
+        self.layout_winit_outputs(&outputs, backend.window_size());
+        outputs.iter().for_each(|output| {
+            self.add_output(output);
+            self.emit_ipc_event(Event::OutputAdded(self.output_info(output)));
+            self.add_output_head(output);
+        });
 
-        let output = Output::new(
-            "winit".into(),
-            PhysicalProperties {
-                size: (0, 0).into(),
-                subpixel: Subpixel::Unknown,
-                make: "PocoWM".into(),
-                model: "Winit".into(),
-            },
-        );
-        output.create_global::<PocoWM>(&self.display);
-        output.change_current_state(
-            Some(mode),
-            Some(Transform::Flipped180),
-            None,
-            Some((0, 0).into()),
-        );
-        output.set_preferred(mode);
-        self.renderer.map_output(&output, (0, 0));
-
-        let mut damage_tracker = OutputDamageTracker::from_output(&output);
-
-        std::env::set_var("WAYLAND_DISPLAY", &self.socket_name);
+        // The outputs are damaged separately, then drawn together into the window
+        let trackers = move |size: Size<i32, Physical>| {
+            (0..count)
+                .map(|i| {
+                    OutputDamageTracker::new(
+                        output_area(size, i, count).0.size,
+                        1.0,
+                        Transform::Normal,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut damage_trackers = trackers(backend.window_size());
 
         self.event_loop
             .borrow_mut()
@@ -50,47 +178,61 @@ impl PocoWM {
                 let result = (|| {
                     match event {
                         WinitEvent::Resized { size, .. } => {
-                            output.change_current_state(
-                                Some(Mode {
-                                    size,
-                                    refresh: 60_000,
-                                }),
-                                None,
-                                None,
-                                None,
-                            );
-                            state.renderer.render(&state.layout);
+                            damage_trackers = trackers(size);
+                            state.layout_winit_outputs(&outputs, size);
+                            state.render_outputs();
+                            outputs.iter().for_each(|output| {
+                                state
+                                    .emit_ipc_event(Event::OutputChanged(state.output_info(output)))
+                            });
+                            state.refresh_output_heads();
                         }
                         WinitEvent::Input(event) => {
                             state.handle_input(event);
                         }
                         WinitEvent::Redraw => {
-                            let damage = Rectangle::from_size(backend.window_size());
+                            let size = backend.window_size();
                             backend.bind().context("Failed to bind winit")?;
-                            render_output::<_, WaylandSurfaceRenderElement<GlesRenderer>, _, _>(
-                                &output,
-                                backend.renderer(),
-                                1.0,
-                                0,
-                                [&state.renderer.space],
-                                &[],
-                                &mut damage_tracker,
-                                // [0.1, 0.1, 0.1, 1.0],
-                                [0.8, 0.8, 0.8, 1.0],
-                            )
-                            .context("Failed to render output")?;
-                            backend
-                                .submit(Some(&[damage]))
-                                .context("Failed to submit")?;
-
-                            state.layout.iter_windows().for_each(|window| {
-                                window.send_frame(
-                                    &output,
-                                    state.start_time.elapsed(),
-                                    Some(Duration::ZERO),
-                                    |_, _| Some(output.clone()),
-                                )
-                            });
+                            let age = backend.buffer_age().unwrap_or(0);
+                            let mut elements = Vec::<WinitElement>::new();
+                            let mut damage = Vec::<Rectangle<i32, Physical>>::new();
+                            for (i, output) in outputs.iter().enumerate() {
+                                let (mode, offset) = output_area(size, i, count);
+                                let area = Rectangle::new(offset, mode.size);
+                                let output_elements =
+                                    state.renderer.output_elements(backend.renderer(), output);
+                                let (output_damage, _) = damage_trackers[i]
+                                    .damage_output(age, &output_elements)
+                                    .map_err(|err| anyhow!("{:#?}", err))
+                                    .context("Failed to damage output")?;
+                                damage.extend(
+                                    output_damage
+                                        .iter()
+                                        .flat_map(|rects| rects.iter())
+                                        .map(|rect| Rectangle::new(rect.loc + area.loc, rect.size)),
+                                );
+                                elements.extend(
+                                    output_elements
+                                        .into_iter()
+                                        .map(|element| {
+                                            RelocateRenderElement::from_element(
+                                                element,
+                                                area.loc,
+                                                Relocate::Relative,
+                                            )
+                                        })
+                                        .filter_map(|element| {
+                                            CropRenderElement::from_element(element, 1.0, area)
+                                        }),
+                                );
+                            }
+
+                            if !damage.is_empty() {
+                                draw_damage(backend.renderer(), size, &elements, &damage)?;
+                                backend.submit(Some(&damage)).context("Failed to submit")?;
+                            }
+
+                            outputs.iter().for_each(|output| state.send_frames(output));
 
                             state.renderer.refresh();
                             state.popups.cleanup();
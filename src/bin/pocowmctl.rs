@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Context as _, Result};
+use pocowm::ipc::protocol::{
+    ContainerInfo, EventType, Geometry, Node, OutputInfo, Request, Response, WindowInfo,
+    WorkspaceInfo,
+};
+use pocowm::ipc::{socket_path, SOCKET_ENV};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: pocowmctl [--socket PATH] [--json] <COMMAND>
+
+Commands:
+  tree                  Print the layout tree
+  windows               List windows
+  outputs               List outputs
+  workspaces            List workspaces
+  subscribe [EVENT...]  Print events as they happen, every type if none is given
+                        (window, focus, layout, window_state, output, workspace)
+  <ACTION>              Run an action, e.g. `focus left`, `layout tabbed` or `workspace 2`";
+
+struct Args {
+    socket: Option<PathBuf>,
+    json: bool,
+    command: Vec<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self> {
+        let mut args = Self {
+            socket: None,
+            json: false,
+            command: Vec::new(),
+        };
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-h" | "--help" if args.command.is_empty() => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                "--json" if args.command.is_empty() => args.json = true,
+                "--socket" if args.command.is_empty() => {
+                    let path = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("--socket needs a path"))?;
+                    args.socket = Some(path.into());
+                }
+                _ => args.command.push(arg),
+            }
+        }
+        if args.command.is_empty() {
+            return Err(anyhow!("Missing command\n\n{}", USAGE));
+        }
+        Ok(args)
+    }
+
+    /// Finds the socket of the instance we were started from, like clients find the wayland one
+    fn socket(&self) -> Result<PathBuf> {
+        if let Some(socket) = &self.socket {
+            return Ok(socket.clone());
+        }
+        if let Some(socket) = std::env::var_os(SOCKET_ENV).filter(|s| !s.is_empty()) {
+            return Ok(socket.into());
+        }
+        let display = std::env::var_os("WAYLAND_DISPLAY")
+            .filter(|d| !d.is_empty())
+            .ok_or_else(|| anyhow!("Neither {} nor WAYLAND_DISPLAY is set", SOCKET_ENV))?;
+        socket_path(&display).ok_or_else(|| anyhow!("XDG_RUNTIME_DIR is not set"))
+    }
+}
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Connection {
+    fn connect(path: &Path) -> Result<Self> {
+        let writer = UnixStream::connect(path)
+            .with_context(|| format!("Failed to connect to {}", path.to_string_lossy()))?;
+        let reader = BufReader::new(writer.try_clone().context("Failed to connect")?);
+        Ok(Self { reader, writer })
+    }
+
+    fn send(&mut self, request: &Request) -> Result<()> {
+        let mut message = serde_json::to_vec(request).context("Failed to encode request")?;
+        message.push(b'\n');
+        self.writer
+            .write_all(&message)
+            .context("Failed to send request")
+    }
+
+    /// Reads the next line sent by PocoWM, `None` once it closed the connection
+    fn read_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        let n = self
+            .reader
+            .read_line(&mut line)
+            .context("Failed to read from PocoWM")?;
+        Ok((n > 0).then_some(line))
+    }
+
+    fn request(&mut self, request: &Request) -> Result<(String, Response)> {
+        self.send(request)?;
+        let line = self
+            .read_line()?
+            .ok_or_else(|| anyhow!("PocoWM closed the connection"))?;
+        let response = serde_json::from_str(&line).context("Invalid response")?;
+        Ok((line, response))
+    }
+}
+
+fn format_geometry(geometry: Option<Geometry>) -> String {
+    geometry.map_or_else(
+        || "hidden".into(),
+        |g| format!("{},{} {}x{}", g.x, g.y, g.width, g.height),
+    )
+}
+
+fn format_window(window: &WindowInfo) -> String {
+    let mut line = format!(
+        "window {} {:?} ({}) {}",
+        window.id,
+        window.title.as_deref().unwrap_or_default(),
+        window.app_id.as_deref().unwrap_or("no app_id"),
+        format_geometry(window.geometry),
+    );
+    if !window.state.is_empty() {
+        line += &format!(" [{}]", window.state.join(", "));
+    }
+    if window.focused {
+        line += " *";
+    }
+    line
+}
+
+fn print_node(node: &Node, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match node {
+        Node::Container(ContainerInfo {
+            id,
+            layout_type,
+            geometry,
+            children,
+            ..
+        }) => {
+            println!(
+                "{}container {} {:?} {}",
+                indent,
+                id,
+                layout_type,
+                format_geometry(*geometry)
+            );
+            children
+                .iter()
+                .for_each(|child| print_node(child, depth + 1));
+        }
+        Node::Window(window) => println!("{}{}", indent, format_window(window)),
+    }
+}
+
+fn print_output(output: &OutputInfo) {
+    let refresh = output
+        .refresh
+        .map(|r| format!(" @ {:.3}Hz", r as f64 / 1000.0))
+        .unwrap_or_default();
+    println!(
+        "{} \"{} {}\" {}{} scale {}",
+        output.name,
+        output.make,
+        output.model,
+        format_geometry(output.geometry),
+        refresh,
+        output.scale
+    );
+}
+
+fn print_workspace(workspace: &WorkspaceInfo) {
+    let mut line = workspace.name.clone();
+    if let Some(output) = &workspace.output {
+        line += &format!(" on {}", output);
+    }
+    if workspace.focused {
+        line += " *";
+    }
+    println!("{}", line);
+}
+
+fn run() -> Result<()> {
+    let args = Args::parse()?;
+    let mut connection = Connection::connect(&args.socket()?)?;
+
+    let request = match args.command[0].as_str() {
+        "tree" => Request::GetTree,
+        "windows" => Request::GetWindows,
+        "outputs" => Request::GetOutputs,
+        "workspaces" => Request::GetWorkspaces,
+        "subscribe" => Request::Subscribe {
+            events: args.command[1..]
+                .iter()
+                .map(|event| {
+                    serde_json::from_value::<EventType>(event.as_str().into())
+                        .map_err(|_| anyhow!("Unknown event type: {}", event))
+                })
+                .collect::<Result<_>>()?,
+        },
+        _ => Request::Command {
+            command: args.command.join(" "),
+        },
+    };
+
+    let (line, response) = connection.request(&request)?;
+    if args.json && !matches!(request, Request::Subscribe { .. }) {
+        print!("{}", line);
+    }
+    match response {
+        Response::Error(err) => return Err(anyhow!(err)),
+        _ if args.json => {}
+        Response::Success => {}
+        Response::Tree(tree) => print_node(&tree, 0),
+        Response::Windows(windows) => windows
+            .iter()
+            .for_each(|window| println!("{}", format_window(window))),
+        Response::Outputs(outputs) => outputs.iter().for_each(print_output),
+        Response::Workspaces(workspaces) => workspaces.iter().for_each(print_workspace),
+    }
+
+    if let Request::Subscribe { .. } = request {
+        while let Some(line) = connection.read_line()? {
+            print!("{}", line);
+            std::io::stdout().flush().context("Failed to write event")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{:?}", err);
+        std::process::exit(1);
+    }
+}
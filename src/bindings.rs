@@ -0,0 +1,133 @@
+use crate::action::{Action, Direction};
+use crate::config::Config;
+use crate::input::KeyModifiers;
+use crate::layout::LayoutType;
+use crate::workspace::WorkspaceTarget;
+use anyhow::anyhow;
+use serde::{Deserialize, Deserializer};
+use smithay::input::keyboard::{xkb, Keysym};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A modifiers+keysym combination, written as `Alt+Shift+Return` in the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub modifiers: KeyModifiers,
+    pub keysym: Keysym,
+}
+
+impl FromStr for KeyCombo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = s.rsplit_once('+').unwrap_or(("", s));
+        let keysym = xkb::keysym_from_name(key.trim(), xkb::KEYSYM_CASE_INSENSITIVE);
+        if keysym == Keysym::NoSymbol {
+            return Err(anyhow!("Unknown key: {}", key));
+        }
+        Ok(Self {
+            modifiers: modifiers.parse()?,
+            keysym,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyCombo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A `[[bindings]]` entry of the config file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BindingConfig {
+    pub keys: KeyCombo,
+    pub action: Action,
+    /// Fire when the key is released instead of pressed
+    #[serde(default)]
+    pub release: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Binding {
+    combo: KeyCombo,
+    release: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bindings(HashMap<Binding, Action>);
+
+impl Bindings {
+    pub fn from_config(config: &Config) -> Self {
+        let mut bindings = Self::default();
+        if config.general.default_bindings {
+            bindings.insert_defaults(config);
+        }
+        config.bindings.iter().for_each(|binding| {
+            bindings.insert(binding.keys, binding.release, binding.action.clone());
+        });
+        bindings
+    }
+
+    fn insert(&mut self, combo: KeyCombo, release: bool, action: Action) {
+        self.0.insert(Binding { combo, release }, action);
+    }
+
+    fn insert_defaults(&mut self, config: &Config) {
+        let modifiers = config.general.modifier;
+        let mut bind = |keysym: Keysym, action: Action| {
+            self.insert(KeyCombo { modifiers, keysym }, false, action);
+        };
+        bind(
+            Keysym::Return,
+            Action::Spawn(config.general.terminal.clone()),
+        );
+        bind(Keysym::e, Action::ToggleLayout);
+        bind(Keysym::h, Action::Focus(Direction::Left));
+        bind(Keysym::j, Action::Focus(Direction::Down));
+        bind(Keysym::k, Action::Focus(Direction::Up));
+        bind(Keysym::l, Action::Focus(Direction::Right));
+        bind(Keysym::b, Action::Split(LayoutType::Vertical));
+        bind(Keysym::n, Action::Split(LayoutType::Horizontal));
+        bind(Keysym::f, Action::ToggleFloating);
+        bind(Keysym::m, Action::ToggleMaximize);
+        bind(Keysym::u, Action::Unminimize(None));
+        bind(Keysym::equal, Action::Resize(10));
+        bind(Keysym::minus, Action::Resize(-10));
+        bind(Keysym::_0, Action::Equalize);
+        (1..=9).for_each(|i| {
+            let target = WorkspaceTarget::Name(i.to_string());
+            bind(Keysym::new(Keysym::_0.raw() + i), Action::Workspace(target));
+        });
+
+        let modifiers = modifiers | KeyModifiers::SHIFT;
+        let mut bind = |keysym: Keysym, action: Action| {
+            self.insert(KeyCombo { modifiers, keysym }, false, action);
+        };
+        bind(Keysym::q, Action::Close);
+        bind(Keysym::f, Action::ToggleFullscreen);
+        bind(Keysym::m, Action::Minimize);
+        bind(Keysym::u, Action::PickMinimized);
+        bind(Keysym::c, Action::ReloadConfig);
+        bind(Keysym::e, Action::Quit);
+        (1..=9).for_each(|i| {
+            let target = WorkspaceTarget::Name(i.to_string());
+            bind(
+                Keysym::new(Keysym::_0.raw() + i),
+                Action::MoveToWorkspace(target),
+            );
+        });
+    }
+
+    /// Finds the action bound to a key event, `syms` being the unmodified keysyms of the key
+    pub fn find(&self, modifiers: KeyModifiers, syms: &[Keysym], release: bool) -> Option<&Action> {
+        syms.iter().find_map(|&keysym| {
+            self.0.get(&Binding {
+                combo: KeyCombo { modifiers, keysym },
+                release,
+            })
+        })
+    }
+}
@@ -0,0 +1,420 @@
+use crate::bindings::{BindingConfig, Bindings};
+pub use crate::input::KeyModifiers;
+use crate::theme::Theme;
+use crate::PocoWM;
+use anyhow::{anyhow, Context as _, Result};
+use serde::{Deserialize, Deserializer};
+use smithay::output::Output;
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::utils::{Physical, Size, Transform};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// RGBA color, written as `#rrggbb` or `#rrggbbaa` in the config file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color(pub [f32; 4]);
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s
+            .strip_prefix('#')
+            .ok_or_else(|| anyhow!("Color must start with '#': {}", s))?;
+        if hex.len() != 6 && hex.len() != 8 {
+            return Err(anyhow!("Color must be #rrggbb or #rrggbbaa: {}", s));
+        }
+        let mut color = [1.0; 4];
+        for (i, c) in color.iter_mut().enumerate().take(hex.len() / 2) {
+            let byte = hex
+                .get(i * 2..i * 2 + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid color: {}", s))?;
+            *c = byte as f32 / 255.0;
+        }
+        Ok(Self(color))
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneralConfig {
+    /// Gap between tiled windows and around the output, in logical pixels
+    pub gap: i32,
+    /// Command spawned by the terminal binding
+    pub terminal: String,
+    /// Modifier used by the default bindings and to move or resize windows with the mouse
+    pub modifier: KeyModifiers,
+    /// Whether the built-in bindings are kept, `[[bindings]]` entries override them anyway
+    pub default_bindings: bool,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            gap: 20,
+            terminal: "kitty".into(),
+            modifier: KeyModifiers::ALT,
+            default_bindings: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyboardConfig {
+    /// Delay before a held key starts repeating, in milliseconds
+    pub repeat_delay: i32,
+    /// Repeated keys per second
+    pub repeat_rate: i32,
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            repeat_delay: 200,
+            repeat_rate: 25,
+        }
+    }
+}
+
+/// Tab bar of tabbed containers
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TabsConfig {
+    pub height: i32,
+    pub active: Color,
+    pub inactive: Color,
+}
+
+impl Default for TabsConfig {
+    fn default() -> Self {
+        Self {
+            height: 24,
+            active: Color([0.0, 0.0, 1.0, 1.0]),
+            inactive: Color([0.3, 0.3, 0.3, 1.0]),
+        }
+    }
+}
+
+/// `[borders]` of older config files, read into the `[theme]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LegacyBordersConfig {
+    pub size: Option<u32>,
+    /// Border of the focused window
+    pub color: Option<Color>,
+}
+
+/// `[decorations]` of older config files, read into the `[theme]`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LegacyDecorationsConfig {
+    /// Title bar of the focused window
+    pub background: Option<Color>,
+    pub inactive_background: Option<Color>,
+    pub title: Option<Color>,
+    pub inactive_title: Option<Color>,
+    pub font: Option<String>,
+    pub font_size: Option<f32>,
+    pub app_id: Option<bool>,
+    pub close_button: Option<Color>,
+    pub maximize_button: Option<Color>,
+    pub minimize_button: Option<Color>,
+}
+
+/// Rotation and flip of an output, named like in wlr-randr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum OutputTransform {
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "90")]
+    Rotate90,
+    #[serde(rename = "180")]
+    Rotate180,
+    #[serde(rename = "270")]
+    Rotate270,
+    #[serde(rename = "flipped")]
+    Flipped,
+    #[serde(rename = "flipped-90")]
+    Flipped90,
+    #[serde(rename = "flipped-180")]
+    Flipped180,
+    #[serde(rename = "flipped-270")]
+    Flipped270,
+}
+
+impl From<OutputTransform> for Transform {
+    fn from(value: OutputTransform) -> Self {
+        match value {
+            OutputTransform::Normal => Self::Normal,
+            OutputTransform::Rotate90 => Self::_90,
+            OutputTransform::Rotate180 => Self::_180,
+            OutputTransform::Rotate270 => Self::_270,
+            OutputTransform::Flipped => Self::Flipped,
+            OutputTransform::Flipped90 => Self::Flipped90,
+            OutputTransform::Flipped180 => Self::Flipped180,
+            OutputTransform::Flipped270 => Self::Flipped270,
+        }
+    }
+}
+
+/// Size and refresh rate of an output, written as `WIDTHxHEIGHT[@HZ]`, e.g. `1280x720@30`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputMode {
+    pub size: Size<i32, Physical>,
+    /// Refresh rate in mHz, the current one is kept if it isn't given
+    pub refresh: Option<i32>,
+}
+
+impl FromStr for OutputMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (size, refresh) = match s.split_once('@') {
+            Some((size, refresh)) => {
+                let refresh = refresh
+                    .parse::<f64>()
+                    .with_context(|| format!("Invalid refresh rate: {}", refresh))?;
+                (size, Some((refresh * 1000.0).round() as i32))
+            }
+            None => (s, None),
+        };
+        let (w, h) = size
+            .split_once('x')
+            .ok_or_else(|| anyhow!("Invalid output size: {}", size))?;
+        let w = w.parse().with_context(|| format!("Invalid width: {}", w))?;
+        let h = h
+            .parse()
+            .with_context(|| format!("Invalid height: {}", h))?;
+        if w <= 0 || h <= 0 || refresh.is_some_and(|refresh| refresh <= 0) {
+            return Err(anyhow!("Invalid output mode: {}", s));
+        }
+        Ok(Self {
+            size: (w, h).into(),
+            refresh,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// An `[[outputs]]` entry, applying to the outputs matched by `name`, or by `make` and `model`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub name: Option<String>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    /// Integer or fractional scale
+    pub scale: Option<f64>,
+    pub transform: Option<OutputTransform>,
+    /// Location in the global space, in logical pixels
+    pub position: Option<OutputPosition>,
+    /// Ignored for the winit outputs, whose size follows the window
+    pub mode: Option<OutputMode>,
+}
+
+impl OutputConfig {
+    /// Whether every criterion given matches `output`, an entry without any matching nothing
+    pub fn matches(&self, output: &Output) -> bool {
+        let properties = output.physical_properties();
+        let criteria = [
+            (&self.name, output.name()),
+            (&self.make, properties.make),
+            (&self.model, properties.model),
+        ];
+        criteria.iter().any(|(expected, _)| expected.is_some())
+            && criteria
+                .iter()
+                .all(|(expected, value)| expected.as_ref().is_none_or(|e| e == value))
+    }
+}
+
+/// Content of `$XDG_CONFIG_HOME/pocowm/config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub keyboard: KeyboardConfig,
+    pub theme: Theme,
+    /// Other themes to switch to at runtime, by name
+    pub themes: HashMap<String, Theme>,
+    pub tabs: TabsConfig,
+    pub outputs: Vec<OutputConfig>,
+    pub bindings: Vec<BindingConfig>,
+    /// Deprecated, moved into `theme` once parsed
+    pub borders: Option<LegacyBordersConfig>,
+    /// Deprecated, moved into `theme` once parsed
+    pub decorations: Option<LegacyDecorationsConfig>,
+    /// Name of the theme switched to at runtime, kept across reloads
+    #[serde(skip)]
+    pub active_theme: Option<String>,
+}
+
+impl Config {
+    /// The theme switched to at runtime, or the `[theme]` of the config file
+    pub fn theme(&self) -> &Theme {
+        self.active_theme
+            .as_ref()
+            .and_then(|name| self.themes.get(name))
+            .unwrap_or(&self.theme)
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("pocowm").join("config.toml"))
+    }
+
+    /// Loads the config file, falling back to the defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Failed to read {}", path.to_string_lossy()));
+            }
+        };
+        Self::parse(&content).with_context(|| format!("Failed to parse {}", path.to_string_lossy()))
+    }
+
+    /// Parses the content of a config file, the sections of older versions included
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config = toml::from_str::<Self>(content)?;
+        config.migrate();
+        Ok(config)
+    }
+
+    /// Moves the deprecated `[borders]` and `[decorations]` into the `[theme]`
+    fn migrate(&mut self) {
+        let theme = &mut self.theme;
+        if let Some(borders) = self.borders.take() {
+            eprintln!("[borders] is deprecated, use border_width and focused.border in [theme]");
+            if let Some(size) = borders.size {
+                theme.border_width = size;
+            }
+            if let Some(color) = borders.color {
+                theme.focused.border = color;
+            }
+        }
+        if let Some(decorations) = self.decorations.take() {
+            eprintln!("[decorations] is deprecated, use [theme]");
+            let colors = [
+                (decorations.background, &mut theme.focused.background),
+                (
+                    decorations.inactive_background,
+                    &mut theme.unfocused.background,
+                ),
+                (decorations.title, &mut theme.focused.title),
+                (decorations.inactive_title, &mut theme.unfocused.title),
+                (decorations.close_button, &mut theme.close_button),
+                (decorations.maximize_button, &mut theme.maximize_button),
+                (decorations.minimize_button, &mut theme.minimize_button),
+            ];
+            for (color, field) in colors {
+                if let Some(color) = color {
+                    *field = color;
+                }
+            }
+            if let Some(font) = decorations.font {
+                theme.font = font;
+            }
+            if let Some(size) = decorations.font_size {
+                theme.font_size = size;
+            }
+            if let Some(app_id) = decorations.app_id {
+                theme.app_id = app_id;
+            }
+        }
+    }
+}
+
+fn modified_time() -> Option<SystemTime> {
+    Config::path()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok())
+}
+
+impl PocoWM {
+    /// Polls the config file and reloads it whenever it changes
+    pub(crate) fn init_config_watcher(&mut self) -> Result<()> {
+        let mut last_modified = modified_time();
+        self.loop_handle
+            .insert_source(Timer::from_duration(WATCH_INTERVAL), move |_, _, state| {
+                let modified = modified_time();
+                if modified != last_modified {
+                    last_modified = modified;
+                    if let Err(err) = state.reload_config() {
+                        eprintln!("{:?}\nKeeping the previous config", err);
+                    }
+                }
+                TimeoutAction::ToDuration(WATCH_INTERVAL)
+            })
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("Failed to insert config watcher")?;
+        Ok(())
+    }
+
+    /// Reloads the config file, keeping the current config if it is invalid
+    pub fn reload_config(&mut self) -> Result<()> {
+        let mut config = Config::load()?;
+        config.active_theme = self.config.active_theme.clone();
+        self.apply_config(config);
+        Ok(())
+    }
+
+    pub fn apply_config(&mut self, config: Config) {
+        if *self.config == config {
+            return;
+        }
+        self.config = Rc::new(config);
+        if let Some(keyboard) = self.seat.get_keyboard() {
+            keyboard.change_repeat_info(
+                self.config.keyboard.repeat_rate,
+                self.config.keyboard.repeat_delay,
+            );
+        }
+        self.bindings = Bindings::from_config(&self.config);
+        self.renderer.config = self.config.clone();
+        let inactive = self.workspaces.iter();
+        self.layout
+            .iter_windows()
+            .chain(inactive.flat_map(|workspace| workspace.layout.iter_windows()))
+            .for_each(|window| *window.config_mut() = self.config.clone());
+        self.configure_outputs();
+        self.render_outputs();
+    }
+}
@@ -0,0 +1,368 @@
+use crate::window::Window;
+use crate::PocoWM;
+use smithay::backend::input::KeyState;
+use smithay::desktop::LayerSurface;
+use smithay::input::keyboard::{self, KeyboardTarget, KeysymHandle};
+use smithay::input::pointer::{self, PointerTarget};
+use smithay::input::touch::{self, TouchTarget};
+use smithay::input::Seat;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{IsAlive, Serial};
+use smithay::wayland::seat::WaylandFocus;
+use std::borrow::Cow;
+
+/// Anything the seat can focus: windows of the layout, or layer surfaces like panels and
+/// launchers
+#[derive(Debug, Clone, PartialEq)]
+pub enum FocusTarget {
+    Window(Window),
+    Layer(LayerSurface),
+}
+
+impl FocusTarget {
+    pub fn window(&self) -> Option<&Window> {
+        match self {
+            Self::Window(window) => Some(window),
+            Self::Layer(_) => None,
+        }
+    }
+    pub fn into_window(self) -> Option<Window> {
+        match self {
+            Self::Window(window) => Some(window),
+            Self::Layer(_) => None,
+        }
+    }
+}
+
+impl From<Window> for FocusTarget {
+    fn from(window: Window) -> Self {
+        Self::Window(window)
+    }
+}
+
+impl From<LayerSurface> for FocusTarget {
+    fn from(layer: LayerSurface) -> Self {
+        Self::Layer(layer)
+    }
+}
+
+impl IsAlive for FocusTarget {
+    fn alive(&self) -> bool {
+        match self {
+            Self::Window(window) => window.alive(),
+            Self::Layer(layer) => layer.alive(),
+        }
+    }
+}
+
+impl WaylandFocus for FocusTarget {
+    fn wl_surface(&self) -> Option<Cow<'_, WlSurface>> {
+        match self {
+            Self::Window(window) => window.wl_surface(),
+            Self::Layer(layer) => Some(Cow::Borrowed(layer.wl_surface())),
+        }
+    }
+}
+
+impl PointerTarget<PocoWM> for FocusTarget {
+    fn enter(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::MotionEvent) {
+        match self {
+            Self::Window(window) => PointerTarget::enter(window, seat, data, event),
+            Self::Layer(layer) => PointerTarget::enter(layer.wl_surface(), seat, data, event),
+        }
+    }
+
+    fn motion(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::MotionEvent) {
+        match self {
+            Self::Window(window) => PointerTarget::motion(window, seat, data, event),
+            Self::Layer(layer) => PointerTarget::motion(layer.wl_surface(), seat, data, event),
+        }
+    }
+
+    fn relative_motion(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::RelativeMotionEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::relative_motion(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::relative_motion(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn button(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::ButtonEvent) {
+        match self {
+            Self::Window(window) => PointerTarget::button(window, seat, data, event),
+            Self::Layer(layer) => PointerTarget::button(layer.wl_surface(), seat, data, event),
+        }
+    }
+
+    fn axis(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, frame: pointer::AxisFrame) {
+        match self {
+            Self::Window(window) => PointerTarget::axis(window, seat, data, frame),
+            Self::Layer(layer) => PointerTarget::axis(layer.wl_surface(), seat, data, frame),
+        }
+    }
+
+    fn frame(&self, seat: &Seat<PocoWM>, data: &mut PocoWM) {
+        match self {
+            Self::Window(window) => PointerTarget::frame(window, seat, data),
+            Self::Layer(layer) => PointerTarget::frame(layer.wl_surface(), seat, data),
+        }
+    }
+
+    fn leave(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, serial: Serial, time: u32) {
+        match self {
+            Self::Window(window) => PointerTarget::leave(window, seat, data, serial, time),
+            Self::Layer(layer) => {
+                PointerTarget::leave(layer.wl_surface(), seat, data, serial, time)
+            }
+        }
+    }
+
+    fn gesture_swipe_begin(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GestureSwipeBeginEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_swipe_begin(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_swipe_begin(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_swipe_update(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GestureSwipeUpdateEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_swipe_update(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_swipe_update(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_swipe_end(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GestureSwipeEndEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_swipe_end(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_swipe_end(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_begin(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GesturePinchBeginEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_pinch_begin(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_pinch_begin(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_update(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GesturePinchUpdateEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_pinch_update(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_pinch_update(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_end(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GesturePinchEndEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_pinch_end(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_pinch_end(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_hold_begin(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GestureHoldBeginEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_hold_begin(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_hold_begin(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_hold_end(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &pointer::GestureHoldEndEvent,
+    ) {
+        match self {
+            Self::Window(window) => PointerTarget::gesture_hold_end(window, seat, data, event),
+            Self::Layer(layer) => {
+                PointerTarget::gesture_hold_end(layer.wl_surface(), seat, data, event)
+            }
+        }
+    }
+}
+
+impl TouchTarget<PocoWM> for FocusTarget {
+    fn down(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &touch::DownEvent, seq: Serial) {
+        match self {
+            Self::Window(window) => TouchTarget::down(window, seat, data, event, seq),
+            Self::Layer(layer) => TouchTarget::down(layer.wl_surface(), seat, data, event, seq),
+        }
+    }
+
+    fn up(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &touch::UpEvent, seq: Serial) {
+        match self {
+            Self::Window(window) => TouchTarget::up(window, seat, data, event, seq),
+            Self::Layer(layer) => TouchTarget::up(layer.wl_surface(), seat, data, event, seq),
+        }
+    }
+
+    fn motion(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &touch::MotionEvent,
+        seq: Serial,
+    ) {
+        match self {
+            Self::Window(window) => TouchTarget::motion(window, seat, data, event, seq),
+            Self::Layer(layer) => TouchTarget::motion(layer.wl_surface(), seat, data, event, seq),
+        }
+    }
+
+    fn frame(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, seq: Serial) {
+        match self {
+            Self::Window(window) => TouchTarget::frame(window, seat, data, seq),
+            Self::Layer(layer) => TouchTarget::frame(layer.wl_surface(), seat, data, seq),
+        }
+    }
+
+    fn cancel(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, seq: Serial) {
+        match self {
+            Self::Window(window) => TouchTarget::cancel(window, seat, data, seq),
+            Self::Layer(layer) => TouchTarget::cancel(layer.wl_surface(), seat, data, seq),
+        }
+    }
+
+    fn shape(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &touch::ShapeEvent,
+        seq: Serial,
+    ) {
+        match self {
+            Self::Window(window) => TouchTarget::shape(window, seat, data, event, seq),
+            Self::Layer(layer) => TouchTarget::shape(layer.wl_surface(), seat, data, event, seq),
+        }
+    }
+
+    fn orientation(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        event: &touch::OrientationEvent,
+        seq: Serial,
+    ) {
+        match self {
+            Self::Window(window) => TouchTarget::orientation(window, seat, data, event, seq),
+            Self::Layer(layer) => {
+                TouchTarget::orientation(layer.wl_surface(), seat, data, event, seq)
+            }
+        }
+    }
+}
+
+impl KeyboardTarget<PocoWM> for FocusTarget {
+    fn enter(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        keys: Vec<KeysymHandle<'_>>,
+        serial: Serial,
+    ) {
+        match self {
+            Self::Window(window) => KeyboardTarget::enter(window, seat, data, keys, serial),
+            Self::Layer(layer) => {
+                KeyboardTarget::enter(layer.wl_surface(), seat, data, keys, serial)
+            }
+        }
+    }
+
+    fn leave(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, serial: Serial) {
+        match self {
+            Self::Window(window) => KeyboardTarget::leave(window, seat, data, serial),
+            Self::Layer(layer) => KeyboardTarget::leave(layer.wl_surface(), seat, data, serial),
+        }
+    }
+
+    fn key(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        key: KeysymHandle<'_>,
+        state: KeyState,
+        serial: Serial,
+        time: u32,
+    ) {
+        match self {
+            Self::Window(window) => {
+                KeyboardTarget::key(window, seat, data, key, state, serial, time)
+            }
+            Self::Layer(layer) => {
+                KeyboardTarget::key(layer.wl_surface(), seat, data, key, state, serial, time)
+            }
+        }
+    }
+
+    fn modifiers(
+        &self,
+        seat: &Seat<PocoWM>,
+        data: &mut PocoWM,
+        modifiers: keyboard::ModifiersState,
+        serial: Serial,
+    ) {
+        match self {
+            Self::Window(window) => {
+                KeyboardTarget::modifiers(window, seat, data, modifiers, serial)
+            }
+            Self::Layer(layer) => {
+                KeyboardTarget::modifiers(layer.wl_surface(), seat, data, modifiers, serial)
+            }
+        }
+    }
+}
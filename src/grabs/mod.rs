@@ -1,5 +1,7 @@
 pub mod move_grab;
 pub mod resize_grab;
+pub mod tiled_resize_grab;
 
 pub use move_grab::MoveGrab;
 pub use resize_grab::{ResizeGrab, ResizeState};
+pub use tiled_resize_grab::{SplitDrag, TiledResizeGrab};
@@ -1,3 +1,4 @@
+use crate::focus::FocusTarget;
 use crate::window::{Window, WindowState};
 use crate::PocoWM;
 use smithay::desktop::space::SpaceElement;
@@ -7,6 +8,7 @@ use smithay::input::pointer::{
     GestureSwipeUpdateEvent, GrabStartData, MotionEvent, PointerGrab, PointerInnerHandle,
     RelativeMotionEvent,
 };
+use smithay::output::Output;
 use smithay::utils::{Logical, Point};
 
 pub struct MoveGrab {
@@ -40,12 +42,24 @@ impl MoveGrab {
     }
 }
 
+impl MoveGrab {
+    /// Moves the window to the workspace shown on `output`, where it was dropped
+    fn move_to_output(&self, data: &mut PocoWM, output: &Output) -> Option<()> {
+        let name = data.workspaces.get_on_output(output)?.name.clone();
+        let id = data.layout.get_window_id(&self.window)?;
+        data.move_window_to_workspace(id, name);
+        data.activate_output(output);
+        data.focus_window(data.layout.get_window_id(&self.window));
+        Some(())
+    }
+}
+
 impl PointerGrab<PocoWM> for MoveGrab {
     fn motion(
         &mut self,
         data: &mut PocoWM,
         handle: &mut PointerInnerHandle<'_, PocoWM>,
-        _focus: Option<(Window, Point<f64, Logical>)>,
+        _focus: Option<(FocusTarget, Point<f64, Logical>)>,
         event: &MotionEvent,
     ) {
         handle.motion(data, None, event);
@@ -64,7 +78,7 @@ impl PointerGrab<PocoWM> for MoveGrab {
         &mut self,
         data: &mut PocoWM,
         handle: &mut PointerInnerHandle<'_, PocoWM>,
-        focus: Option<(Window, Point<f64, Logical>)>,
+        focus: Option<(FocusTarget, Point<f64, Logical>)>,
         event: &RelativeMotionEvent,
     ) {
         handle.relative_motion(data, focus, event);
@@ -176,8 +190,20 @@ impl PointerGrab<PocoWM> for MoveGrab {
     }
 
     fn unset(&mut self, data: &mut PocoWM) {
+        data.renderer.cursor.grab_icon = None;
         let is_floating = self.window.state().contains(WindowState::FLOATING);
-        if !is_floating {
+        let output = data
+            .renderer
+            .output_under(self.pointer_location)
+            .next()
+            .cloned()
+            .filter(|output| data.layout.output.as_ref() != Some(output));
+        if let Some(output) = output {
+            self.move_to_output(data, &output);
+        }
+        if is_floating {
+            self.window.floating_rect_mut().loc = self.new_location;
+        } else {
             self.unset_tiled(data);
         }
         data.renderer.render(&data.layout);
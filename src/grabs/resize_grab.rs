@@ -1,3 +1,4 @@
+use crate::focus::FocusTarget;
 use crate::utils::Edge;
 use crate::window::{Window, WindowState};
 use crate::PocoWM;
@@ -70,7 +71,7 @@ impl PointerGrab<PocoWM> for ResizeGrab {
         &mut self,
         data: &mut PocoWM,
         handle: &mut PointerInnerHandle<'_, PocoWM>,
-        _focus: Option<(Window, Point<f64, Logical>)>,
+        _focus: Option<(FocusTarget, Point<f64, Logical>)>,
         event: &MotionEvent,
     ) {
         handle.motion(data, None, event);
@@ -124,7 +125,7 @@ impl PointerGrab<PocoWM> for ResizeGrab {
         &mut self,
         data: &mut PocoWM,
         handle: &mut PointerInnerHandle<'_, PocoWM>,
-        focus: Option<(Window, Point<f64, Logical>)>,
+        focus: Option<(FocusTarget, Point<f64, Logical>)>,
         event: &RelativeMotionEvent,
     ) {
         handle.relative_motion(data, focus, event);
@@ -232,7 +233,8 @@ impl PointerGrab<PocoWM> for ResizeGrab {
         &self.start_data
     }
 
-    fn unset(&mut self, _data: &mut PocoWM) {
+    fn unset(&mut self, data: &mut PocoWM) {
+        data.renderer.cursor.grab_icon = None;
         let Some(xdg) = self.window.toplevel() else {
             return;
         };
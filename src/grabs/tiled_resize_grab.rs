@@ -0,0 +1,205 @@
+use crate::focus::FocusTarget;
+use crate::layout::{Id, LayoutType, MIN_FRACTION};
+use crate::PocoWM;
+use smithay::input::pointer::{
+    AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+    GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+    GestureSwipeUpdateEvent, GrabStartData, MotionEvent, PointerGrab, PointerInnerHandle,
+    RelativeMotionEvent,
+};
+use smithay::utils::{Logical, Point};
+
+/// The split between two tiled siblings, moved by a `TiledResizeGrab`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitDrag {
+    pub before: Id,
+    pub after: Id,
+    pub layout_type: LayoutType,
+    initial_weights: (f64, f64),
+    min_weight: f64,
+    /// Weight of a pixel of the container
+    weight_per_pixel: f64,
+}
+
+impl SplitDrag {
+    pub fn new(data: &PocoWM, before: Id, after: Id) -> Option<Self> {
+        let parent = data
+            .layout
+            .get_parent(before)
+            .and_then(|id| data.layout.get_sublayout(id))?;
+        let rect = data.renderer.layout_geometry(&data.layout, parent.id)?;
+        let children = data.layout.tiled_children(parent);
+        let count = children.clone().count() as i32;
+        let total_weight = children.map(|(_, el)| el.weight()).sum::<f64>();
+        let length = match parent.layout_type {
+            LayoutType::Vertical => rect.size.h,
+            _ => rect.size.w,
+        } - data.config.general.gap * (count - 1);
+        let weight = |id| data.layout.get_element(id).map(|el| el.weight());
+        Some(Self {
+            before,
+            after,
+            layout_type: parent.layout_type,
+            initial_weights: (weight(before)?, weight(after)?),
+            min_weight: total_weight * MIN_FRACTION,
+            weight_per_pixel: total_weight / length.max(1) as f64,
+        })
+    }
+
+    /// Moves the split by `delta` from where it was when the grab started
+    fn apply(&self, data: &mut PocoWM, delta: Point<f64, Logical>) {
+        let delta = match self.layout_type {
+            LayoutType::Vertical => delta.y,
+            _ => delta.x,
+        } * self.weight_per_pixel;
+        let (before, after) = self.initial_weights;
+        let total = before + after;
+        let min = self.min_weight.min(total / 2.0);
+        let before = (before + delta).clamp(min, total - min);
+        data.layout
+            .get_element_mut(self.before)
+            .map(|el| el.set_weight(before));
+        data.layout
+            .get_element_mut(self.after)
+            .map(|el| el.set_weight(total - before));
+    }
+}
+
+/// Resizes tiled windows by moving the splits between them, at most one per axis
+pub struct TiledResizeGrab {
+    pub start_data: GrabStartData<PocoWM>,
+    pub splits: Vec<SplitDrag>,
+}
+
+impl PointerGrab<PocoWM> for TiledResizeGrab {
+    fn motion(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        _focus: Option<(FocusTarget, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        handle.motion(data, None, event);
+        let delta = event.location - self.start_data.location;
+        self.splits
+            .iter()
+            .for_each(|split| split.apply(data, delta));
+        data.renderer.render(&data.layout);
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        focus: Option<(FocusTarget, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, focus, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(data, event);
+        if !handle.current_pressed().contains(&self.start_data.button) {
+            handle.unset_grab(self, data, event.serial, event.time, true);
+        }
+    }
+
+    fn axis(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        details: AxisFrame,
+    ) {
+        handle.axis(data, details);
+    }
+
+    fn frame(&mut self, data: &mut PocoWM, handle: &mut PointerInnerHandle<'_, PocoWM>) {
+        handle.frame(data);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        handle.gesture_swipe_begin(data, event);
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        handle.gesture_swipe_update(data, event);
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GestureSwipeEndEvent,
+    ) {
+        handle.gesture_swipe_end(data, event);
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GesturePinchBeginEvent,
+    ) {
+        handle.gesture_pinch_begin(data, event);
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        handle.gesture_pinch_update(data, event);
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GesturePinchEndEvent,
+    ) {
+        handle.gesture_pinch_end(data, event);
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GestureHoldBeginEvent,
+    ) {
+        handle.gesture_hold_begin(data, event);
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        data: &mut PocoWM,
+        handle: &mut PointerInnerHandle<'_, PocoWM>,
+        event: &GestureHoldEndEvent,
+    ) {
+        handle.gesture_hold_end(data, event);
+    }
+
+    fn start_data(&self) -> &GrabStartData<PocoWM> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut PocoWM) {
+        data.renderer.cursor.grab_icon = None;
+        data.renderer.render(&data.layout);
+    }
+}
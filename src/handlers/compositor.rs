@@ -32,11 +32,10 @@ impl CompositorHandler for PocoWM {
             while let Some(parent) = get_parent(&root) {
                 root = Cow::Owned(parent);
             }
-            self.layout
-                .get_window_from_surface(&root)
-                .map(|w| w.on_commit());
+            self.find_window(&root).map(|(_, w)| w.on_commit());
         }
         super::xdg_shell::handle_commit(self, surface);
+        super::layer_shell::handle_commit(self, surface);
         crate::grabs::resize_grab::handle_commit(self, surface);
     }
 }
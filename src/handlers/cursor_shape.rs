@@ -0,0 +1,9 @@
+use crate::PocoWM;
+use smithay::delegate_cursor_shape;
+use smithay::wayland::tablet_manager::TabletSeatHandler;
+
+// Shapes set by clients go through `SeatHandler::cursor_image`, tablets have no cursor of
+// their own
+impl TabletSeatHandler for PocoWM {}
+
+delegate_cursor_shape!(PocoWM);
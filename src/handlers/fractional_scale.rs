@@ -0,0 +1,32 @@
+use crate::PocoWM;
+use smithay::delegate_fractional_scale;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::wayland::compositor::{get_parent, with_states};
+use smithay::wayland::fractional_scale::{with_fractional_scale, FractionalScaleHandler};
+use std::borrow::Cow;
+
+impl FractionalScaleHandler for PocoWM {
+    fn new_fractional_scale(&mut self, surface: WlSurface) {
+        let mut root = Cow::Borrowed(&surface);
+        while let Some(parent) = get_parent(&root) {
+            root = Cow::Owned(parent);
+        }
+        let window = self
+            .find_window(&root)
+            .filter(|(_, window)| !window.outputs().is_empty());
+        if let Some((_, window)) = window {
+            window.send_scale();
+            return;
+        }
+        // Not shown yet, the scale of the focused output is the best guess
+        let Some(output) = self.renderer.layout_output(&self.layout) else {
+            return;
+        };
+        let scale = output.current_scale().fractional_scale();
+        with_states(&surface, |states| {
+            with_fractional_scale(states, |fractional| fractional.set_preferred_scale(scale))
+        });
+    }
+}
+
+delegate_fractional_scale!(PocoWM);
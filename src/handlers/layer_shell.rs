@@ -0,0 +1,157 @@
+use crate::focus::FocusTarget;
+use crate::PocoWM;
+use smithay::delegate_layer_shell;
+use smithay::desktop::{layer_map_for_output, LayerSurface, WindowSurfaceType};
+use smithay::output::Output;
+use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{Logical, Point};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::wlr_layer::{
+    KeyboardInteractivity, Layer, LayerSurface as WlrLayerSurface, LayerSurfaceData,
+    WlrLayerShellHandler, WlrLayerShellState,
+};
+
+impl WlrLayerShellHandler for PocoWM {
+    fn shell_state(&mut self) -> &mut WlrLayerShellState {
+        &mut self.layer_shell_state
+    }
+
+    fn new_layer_surface(
+        &mut self,
+        surface: WlrLayerSurface,
+        output: Option<WlOutput>,
+        _layer: Layer,
+        namespace: String,
+    ) {
+        // Surfaces without an output go on the focused one
+        let output = output
+            .as_ref()
+            .and_then(Output::from_resource)
+            .or_else(|| self.renderer.layout_output(&self.layout));
+        let Some(output) = output else {
+            surface.send_close();
+            return;
+        };
+        let result =
+            layer_map_for_output(&output).map_layer(&LayerSurface::new(surface, namespace));
+        if let Err(err) = result {
+            eprintln!("{:?}", err);
+        }
+    }
+
+    fn layer_destroyed(&mut self, surface: WlrLayerSurface) {
+        let Some(output) = self.layer_output(surface.wl_surface()) else {
+            return;
+        };
+        {
+            let mut map = layer_map_for_output(&output);
+            let layer = map
+                .layer_for_surface(surface.wl_surface(), WindowSurfaceType::TOPLEVEL)
+                .cloned();
+            layer.map(|layer| map.unmap_layer(&layer));
+        }
+        // Its exclusive zone and keyboard focus are given back
+        self.render_outputs();
+        self.focus_window(self.layout.get_last_focused_window(self.layout.root().id));
+    }
+}
+delegate_layer_shell!(PocoWM);
+
+/// Sends the initial configure of layer surfaces and arranges their output on every commit
+pub fn handle_commit(state: &mut PocoWM, surface: &WlSurface) {
+    let Some(output) = state.layer_output(surface) else {
+        return;
+    };
+    let initial_configure_sent = with_states(surface, |states| {
+        states
+            .data_map
+            .get::<LayerSurfaceData>()
+            .is_none_or(|data| data.lock().unwrap().initial_configure_sent)
+    });
+    let layer = {
+        let mut map = layer_map_for_output(&output);
+        map.arrange();
+        map.layer_for_surface(surface, WindowSurfaceType::TOPLEVEL)
+            .cloned()
+    };
+    let Some(layer) = layer else {
+        return;
+    };
+    if !initial_configure_sent {
+        layer.layer_surface().send_configure();
+    }
+    state.render_outputs();
+
+    let exclusive = layer.cached_state().keyboard_interactivity == KeyboardInteractivity::Exclusive;
+    let focused = state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus());
+    if exclusive && focused != Some(FocusTarget::Layer(layer)) {
+        state.focus_window(state.layout.get_last_focused_window(state.layout.root().id));
+    }
+}
+
+impl PocoWM {
+    /// Output whose layer map has the layer surface `surface`
+    fn layer_output(&self, surface: &WlSurface) -> Option<Output> {
+        self.renderer
+            .outputs()
+            .find(|output| {
+                layer_map_for_output(output)
+                    .layer_for_surface(surface, WindowSurfaceType::TOPLEVEL)
+                    .is_some()
+            })
+            .cloned()
+    }
+
+    /// Topmost layer surface of `layers` under `location`, along with its location
+    pub fn layer_under(
+        &self,
+        layers: &[Layer],
+        location: Point<f64, Logical>,
+    ) -> Option<(LayerSurface, Point<f64, Logical>)> {
+        let output = self.renderer.output_under(location).next()?;
+        let output_geometry = self.renderer.output_geometry(output)?;
+        let map = layer_map_for_output(output);
+        let local = location - output_geometry.loc.to_f64();
+        layers.iter().find_map(|layer| {
+            let surface = map.layer_under(*layer, local)?;
+            let geometry = map.layer_geometry(surface)?;
+            Some((
+                surface.clone(),
+                (geometry.loc + output_geometry.loc).to_f64(),
+            ))
+        })
+    }
+
+    /// Layers drawn above the windows at `location`, fullscreen windows hiding the top one
+    pub fn upper_layers(&self, location: Point<f64, Logical>) -> &'static [Layer] {
+        let fullscreen = self
+            .renderer
+            .output_under(location)
+            .next()
+            .is_some_and(|output| self.renderer.fullscreen_window(output).is_some());
+        if fullscreen {
+            &[Layer::Overlay]
+        } else {
+            &[Layer::Overlay, Layer::Top]
+        }
+    }
+
+    /// Layer surface above the windows that asked for the keyboard exclusively, if any
+    pub fn exclusive_layer(&self) -> Option<LayerSurface> {
+        self.renderer.outputs().find_map(|output| {
+            let map = layer_map_for_output(output);
+            let layer = map
+                .layers()
+                .filter(|layer| matches!(layer.layer(), Layer::Top | Layer::Overlay))
+                .find(|layer| {
+                    layer.cached_state().keyboard_interactivity == KeyboardInteractivity::Exclusive
+                })
+                .cloned();
+            layer
+        })
+    }
+}
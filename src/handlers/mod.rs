@@ -1,8 +1,14 @@
 mod compositor;
+mod cursor_shape;
 mod data_device;
+mod fractional_scale;
+pub(crate) mod layer_shell;
 mod output;
+pub(crate) mod output_management;
 mod seat;
 mod shm;
+mod viewporter;
+mod xdg_activation;
 mod xdg_decoration;
 mod xdg_foreign;
-mod xdg_shell;
+pub(crate) mod xdg_shell;
@@ -0,0 +1,483 @@
+use crate::ipc::protocol::Event;
+use crate::output::OutputSettings;
+use crate::PocoWM;
+use smithay::output::{Mode, Output, Scale};
+use smithay::reexports::wayland_protocols_wlr::output_management::v1::server::{
+    zwlr_output_configuration_head_v1::{self, ZwlrOutputConfigurationHeadV1},
+    zwlr_output_configuration_v1::{self, ZwlrOutputConfigurationV1},
+    zwlr_output_head_v1::{self, ZwlrOutputHeadV1},
+    zwlr_output_manager_v1::{self, ZwlrOutputManagerV1},
+    zwlr_output_mode_v1::{self, ZwlrOutputModeV1},
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const VERSION: u32 = 4;
+
+/// An output as known by one manager
+#[derive(Debug)]
+struct Head {
+    manager: ZwlrOutputManagerV1,
+    output: Output,
+    head: ZwlrOutputHeadV1,
+    modes: Vec<(Mode, ZwlrOutputModeV1)>,
+}
+
+impl Head {
+    /// Sends the modes the client doesn't know yet, then the current state of the output
+    fn send_state(&mut self, dh: &DisplayHandle, enabled: bool) {
+        let modes = self.output.modes();
+        self.modes.retain(|(mode, resource)| {
+            let keep = modes.contains(mode);
+            if !keep {
+                resource.finished();
+            }
+            keep
+        });
+        let Some(client) = self.head.client() else {
+            return;
+        };
+        for mode in modes {
+            if self.modes.iter().any(|(known, _)| *known == mode) {
+                continue;
+            }
+            let Ok(resource) = client.create_resource::<ZwlrOutputModeV1, _, PocoWM>(
+                dh,
+                self.head.version(),
+                mode,
+            ) else {
+                continue;
+            };
+            self.head.mode(&resource);
+            resource.size(mode.size.w, mode.size.h);
+            resource.refresh(mode.refresh);
+            if self.output.preferred_mode() == Some(mode) {
+                resource.preferred();
+            }
+            self.modes.push((mode, resource));
+        }
+
+        self.head.enabled(enabled as i32);
+        if !enabled {
+            return;
+        }
+        let current = self.output.current_mode();
+        self.modes
+            .iter()
+            .find(|(mode, _)| Some(*mode) == current)
+            .map(|(_, resource)| self.head.current_mode(resource));
+        let location = self.output.current_location();
+        self.head.position(location.x, location.y);
+        self.head.transform(self.output.current_transform().into());
+        self.head
+            .scale(self.output.current_scale().fractional_scale());
+    }
+
+    fn finish(&self) {
+        self.modes
+            .iter()
+            .for_each(|(_, resource)| resource.finished());
+        self.head.finished();
+    }
+}
+
+/// State of `zwlr_output_manager_v1`, which lets tools like wlr-randr and kanshi configure the
+/// outputs
+#[derive(Debug, Default)]
+pub struct OutputManagementState {
+    serial: u32,
+    /// Every output of the backends, the disabled ones included
+    outputs: Vec<Output>,
+    managers: Vec<ZwlrOutputManagerV1>,
+    heads: Vec<Head>,
+}
+
+impl OutputManagementState {
+    pub fn new<D>(dh: &DisplayHandle) -> Self
+    where
+        D: GlobalDispatch<ZwlrOutputManagerV1, ()> + 'static,
+    {
+        dh.create_global::<D, ZwlrOutputManagerV1, ()>(VERSION, ());
+        Self::default()
+    }
+
+    pub fn outputs(&self) -> &[Output] {
+        &self.outputs
+    }
+}
+
+/// Heads of a configuration, with no settings for the ones to disable
+#[derive(Debug)]
+pub struct ConfigurationData {
+    serial: u32,
+    used: AtomicBool,
+    heads: Mutex<Vec<(Output, Option<ZwlrOutputConfigurationHeadV1>)>>,
+}
+
+impl PocoWM {
+    /// Makes an output created by a backend known to output management clients
+    pub(crate) fn add_output_head(&mut self, output: &Output) {
+        self.output_management_state.outputs.push(output.clone());
+        self.refresh_output_heads();
+    }
+
+    /// Sends the state of every output to the clients, under a new serial
+    pub(crate) fn refresh_output_heads(&mut self) {
+        let state = &mut self.output_management_state;
+        state.serial = state.serial.wrapping_add(1);
+        let outputs = &state.outputs;
+        state.heads.retain(|head| {
+            let keep = outputs.contains(&head.output);
+            if !keep {
+                head.finish();
+            }
+            keep
+        });
+        for manager in &state.managers {
+            for output in outputs {
+                let enabled = self.renderer.output_geometry(output).is_some();
+                let head = state
+                    .heads
+                    .iter_mut()
+                    .find(|head| head.manager == *manager && head.output == *output);
+                match head {
+                    Some(head) => head.send_state(&self.display, enabled),
+                    None => {
+                        send_head(&self.display, manager, output, enabled)
+                            .map(|head| state.heads.push(head));
+                    }
+                }
+            }
+            manager.done(state.serial);
+        }
+    }
+
+    fn apply_output_configuration(
+        &mut self,
+        configuration: &ZwlrOutputConfigurationV1,
+        test: bool,
+    ) {
+        let Some(data) = configuration.data::<ConfigurationData>() else {
+            return;
+        };
+        if data.used.swap(true, Ordering::SeqCst) {
+            configuration.post_error(
+                zwlr_output_configuration_v1::Error::AlreadyUsed,
+                "Configuration already applied or tested",
+            );
+            return;
+        }
+        let heads = data
+            .heads
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(output, head)| {
+                let settings = head.as_ref().map(|head| {
+                    head.data::<Mutex<OutputSettings>>()
+                        .map(|settings| *settings.lock().unwrap())
+                        .unwrap_or_default()
+                });
+                (output.clone(), settings)
+            })
+            .collect::<Vec<_>>();
+        let outputs = self.output_management_state.outputs.clone();
+        if outputs
+            .iter()
+            .any(|output| !heads.iter().any(|(configured, _)| configured == output))
+        {
+            configuration.post_error(
+                zwlr_output_configuration_v1::Error::UnconfiguredHead,
+                "Every head must be enabled or disabled",
+            );
+            return;
+        }
+        if data.serial != self.output_management_state.serial {
+            configuration.cancelled();
+            return;
+        }
+
+        let supported = heads.iter().all(|(output, settings)| {
+            settings.is_none_or(|settings| settings.supported_by(output))
+        });
+        // Keep at least one output, there would be nowhere to show the windows otherwise
+        let any_enabled = heads.iter().any(|(_, settings)| settings.is_some());
+        if !supported || !any_enabled {
+            configuration.failed();
+            return;
+        }
+        if test {
+            configuration.succeeded();
+            return;
+        }
+
+        // Disable first, so an output being enabled can take over the active workspace
+        heads
+            .iter()
+            .filter(|(_, settings)| settings.is_none())
+            .for_each(|(output, _)| self.disable_output(output));
+        let mut changed = Vec::new();
+        for (output, settings) in &heads {
+            let Some(settings) = settings else {
+                continue;
+            };
+            let was_enabled = self.renderer.output_geometry(output).is_some();
+            let settings_changed = self.apply_output_settings(output, *settings);
+            self.enable_output(output);
+            // Newly enabled outputs are announced as added instead
+            if settings_changed && was_enabled {
+                changed.push(output.clone());
+            }
+        }
+        self.render_outputs();
+        changed
+            .iter()
+            .for_each(|output| self.emit_ipc_event(Event::OutputChanged(self.output_info(output))));
+        self.refresh_output_heads();
+        configuration.succeeded();
+    }
+}
+
+/// Announces `output` to `manager`, with its current state
+fn send_head(
+    dh: &DisplayHandle,
+    manager: &ZwlrOutputManagerV1,
+    output: &Output,
+    enabled: bool,
+) -> Option<Head> {
+    let client = manager.client()?;
+    let head = client
+        .create_resource::<ZwlrOutputHeadV1, _, PocoWM>(dh, manager.version(), output.clone())
+        .ok()?;
+    manager.head(&head);
+    let properties = output.physical_properties();
+    head.name(output.name());
+    head.description(output.description());
+    head.physical_size(properties.size.w, properties.size.h);
+    if head.version() >= zwlr_output_head_v1::EVT_MAKE_SINCE {
+        head.make(properties.make);
+        head.model(properties.model);
+    }
+    let mut head = Head {
+        manager: manager.clone(),
+        output: output.clone(),
+        head,
+        modes: Vec::new(),
+    };
+    head.send_state(dh, enabled);
+    Some(head)
+}
+
+impl GlobalDispatch<ZwlrOutputManagerV1, ()> for PocoWM {
+    fn bind(
+        state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrOutputManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let manager = data_init.init(resource, ());
+        state.output_management_state.managers.push(manager);
+        state.refresh_output_heads();
+    }
+}
+
+impl Dispatch<ZwlrOutputManagerV1, ()> for PocoWM {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        resource: &ZwlrOutputManagerV1,
+        request: zwlr_output_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            zwlr_output_manager_v1::Request::CreateConfiguration { id, serial } => {
+                data_init.init(
+                    id,
+                    ConfigurationData {
+                        serial,
+                        used: AtomicBool::new(false),
+                        heads: Mutex::new(Vec::new()),
+                    },
+                );
+            }
+            zwlr_output_manager_v1::Request::Stop => {
+                remove_manager(state, resource);
+                resource.finished();
+            }
+            _ => (),
+        }
+    }
+
+    fn destroyed(state: &mut Self, _client: ClientId, resource: &ZwlrOutputManagerV1, _data: &()) {
+        remove_manager(state, resource);
+    }
+}
+
+fn remove_manager(state: &mut PocoWM, manager: &ZwlrOutputManagerV1) {
+    let state = &mut state.output_management_state;
+    state.managers.retain(|known| known != manager);
+    state.heads.retain(|head| head.manager != *manager);
+}
+
+impl Dispatch<ZwlrOutputHeadV1, Output> for PocoWM {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ZwlrOutputHeadV1,
+        _request: zwlr_output_head_v1::Request,
+        _data: &Output,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+
+    fn destroyed(state: &mut Self, _client: ClientId, resource: &ZwlrOutputHeadV1, _data: &Output) {
+        state
+            .output_management_state
+            .heads
+            .retain(|head| head.head != *resource);
+    }
+}
+
+impl Dispatch<ZwlrOutputModeV1, Mode> for PocoWM {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ZwlrOutputModeV1,
+        _request: zwlr_output_mode_v1::Request,
+        _data: &Mode,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+
+    fn destroyed(state: &mut Self, _client: ClientId, resource: &ZwlrOutputModeV1, _data: &Mode) {
+        state
+            .output_management_state
+            .heads
+            .iter_mut()
+            .for_each(|head| head.modes.retain(|(_, mode)| mode != resource));
+    }
+}
+
+impl Dispatch<ZwlrOutputConfigurationV1, ConfigurationData> for PocoWM {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        resource: &ZwlrOutputConfigurationV1,
+        request: zwlr_output_configuration_v1::Request,
+        data: &ConfigurationData,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let (head, config_head) = match request {
+            zwlr_output_configuration_v1::Request::EnableHead { id, head } => {
+                let config_head = data_init.init(id, Mutex::new(OutputSettings::default()));
+                (head, Some(config_head))
+            }
+            zwlr_output_configuration_v1::Request::DisableHead { head } => (head, None),
+            zwlr_output_configuration_v1::Request::Apply => {
+                state.apply_output_configuration(resource, false);
+                return;
+            }
+            zwlr_output_configuration_v1::Request::Test => {
+                state.apply_output_configuration(resource, true);
+                return;
+            }
+            _ => return,
+        };
+        if data.used.load(Ordering::SeqCst) {
+            resource.post_error(
+                zwlr_output_configuration_v1::Error::AlreadyUsed,
+                "Configuration already applied or tested",
+            );
+            return;
+        }
+        let Some(output) = head.data::<Output>() else {
+            return;
+        };
+        let mut heads = data.heads.lock().unwrap();
+        if heads.iter().any(|(configured, _)| configured == output) {
+            resource.post_error(
+                zwlr_output_configuration_v1::Error::AlreadyConfiguredHead,
+                "Head configured twice",
+            );
+            return;
+        }
+        heads.push((output.clone(), config_head));
+    }
+}
+
+impl Dispatch<ZwlrOutputConfigurationHeadV1, Mutex<OutputSettings>> for PocoWM {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        resource: &ZwlrOutputConfigurationHeadV1,
+        request: zwlr_output_configuration_head_v1::Request,
+        data: &Mutex<OutputSettings>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+        use zwlr_output_configuration_head_v1::{Error, Request};
+
+        let mut settings = data.lock().unwrap();
+        let already_set = match request {
+            Request::SetMode { mode } => {
+                let Some(mode) = mode.data::<Mode>() else {
+                    resource.post_error(Error::InvalidMode, "Unknown mode");
+                    return;
+                };
+                settings.mode.replace(*mode).is_some()
+            }
+            Request::SetCustomMode {
+                width,
+                height,
+                refresh,
+            } => {
+                if width <= 0 || height <= 0 || refresh < 0 {
+                    resource.post_error(Error::InvalidCustomMode, "Invalid custom mode");
+                    return;
+                }
+                let mode = Mode {
+                    size: (width, height).into(),
+                    // Zero lets the compositor pick, the only refresh rate is the default one
+                    refresh: if refresh == 0 { 60_000 } else { refresh },
+                };
+                settings.mode.replace(mode).is_some()
+            }
+            Request::SetPosition { x, y } => settings.position.replace((x, y).into()).is_some(),
+            Request::SetTransform { transform } => {
+                let WEnum::Value(transform) = transform else {
+                    resource.post_error(Error::InvalidTransform, "Unknown transform");
+                    return;
+                };
+                settings.transform.replace(transform.into()).is_some()
+            }
+            Request::SetScale { scale } => {
+                if scale <= 0.0 {
+                    resource.post_error(Error::InvalidScale, "Scale must be positive");
+                    return;
+                }
+                let scale = if scale.fract() == 0.0 {
+                    Scale::Integer(scale as i32)
+                } else {
+                    Scale::Fractional(scale)
+                };
+                settings.scale.replace(scale).is_some()
+            }
+            // There is no adaptive sync to configure
+            _ => false,
+        };
+        if already_set {
+            resource.post_error(Error::AlreadySet, "Property already set");
+        }
+    }
+}
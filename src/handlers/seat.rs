@@ -1,4 +1,4 @@
-use crate::window::Window;
+use crate::focus::FocusTarget;
 use crate::PocoWM;
 use smithay::delegate_seat;
 use smithay::input::pointer::CursorImageStatus;
@@ -12,15 +12,17 @@ impl SeatHandler for PocoWM {
     // type PointerFocus = WindowElements;
     // type TouchFocus = WindowElements;
 
-    type KeyboardFocus = Window;
-    type PointerFocus = Window;
-    type TouchFocus = Window;
+    type KeyboardFocus = FocusTarget;
+    type PointerFocus = FocusTarget;
+    type TouchFocus = FocusTarget;
 
     fn seat_state(&mut self) -> &mut SeatState<Self> {
         &mut self.seat_state
     }
 
-    fn cursor_image(&mut self, _seat: &Seat<Self>, _image: CursorImageStatus) {}
+    fn cursor_image(&mut self, _seat: &Seat<Self>, image: CursorImageStatus) {
+        self.renderer.cursor.status = image;
+    }
 
     fn focus_changed(&mut self, seat: &Seat<Self>, focused: Option<&Self::KeyboardFocus>) {
         let client = focused
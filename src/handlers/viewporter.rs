@@ -0,0 +1,4 @@
+use crate::PocoWM;
+use smithay::delegate_viewporter;
+
+delegate_viewporter!(PocoWM);
@@ -0,0 +1,31 @@
+use crate::PocoWM;
+use smithay::delegate_xdg_activation;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::wayland::xdg_activation::{
+    XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
+};
+
+impl XdgActivationHandler for PocoWM {
+    fn activation_state(&mut self) -> &mut XdgActivationState {
+        &mut self.xdg_activation_state
+    }
+
+    /// Windows asking for activation aren't given the focus, only marked as urgent
+    fn request_activation(
+        &mut self,
+        token: XdgActivationToken,
+        _token_data: XdgActivationTokenData,
+        surface: WlSurface,
+    ) {
+        self.xdg_activation_state.remove_token(&token);
+        let Some((_, window)) = self.find_window(&surface) else {
+            return;
+        };
+        if window.is_focused() {
+            return;
+        }
+        *window.urgent_mut() = true;
+        self.render_outputs();
+    }
+}
+delegate_xdg_activation!(PocoWM);
@@ -14,14 +14,14 @@ impl XdgDecorationHandler for PocoWM {
     }
 
     fn request_mode(&mut self, toplevel: ToplevelSurface, mode: Mode) {
-        if let Some(window) = self.layout.get_window_from_surface(toplevel.wl_surface()) {
+        if let Some((_, window)) = self.find_window(toplevel.wl_surface()) {
             match mode {
                 Mode::ServerSide => window.add_decorations(),
                 Mode::ClientSide => window.remove_decorations(),
                 _ => {}
             };
         }
-        self.renderer.render(&self.layout);
+        self.render_outputs();
     }
 }
 
@@ -1,17 +1,21 @@
-use crate::grabs::{MoveGrab, ResizeGrab, ResizeState};
-use crate::layout::{Id, Layout, LayoutElement};
+use crate::grabs::{MoveGrab, ResizeGrab, ResizeState, SplitDrag, TiledResizeGrab};
+use crate::ipc::protocol::Event;
+use crate::layout::{Id, Layout, LayoutElement, LayoutType};
 use crate::utils::Edge;
 use crate::window::{Window, WindowState};
+use crate::workspace::WorkspaceTarget;
 use crate::PocoWM;
 use smithay::delegate_xdg_shell;
 use smithay::desktop::{find_popup_root_surface, get_popup_toplevel_coords, PopupKind};
-use smithay::input::pointer::{Focus, GrabStartData};
+use smithay::input::pointer::{CursorIcon, Focus, GrabStartData};
 use smithay::input::Seat;
+use smithay::output::Output;
 use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::{self};
+use smithay::reexports::wayland_server::protocol::wl_output::WlOutput;
 use smithay::reexports::wayland_server::protocol::wl_seat::WlSeat;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::reexports::wayland_server::Resource as _;
-use smithay::utils::{Rectangle, Serial};
+use smithay::utils::{Logical, Point, Rectangle, Serial};
 use smithay::wayland::compositor::with_states;
 use smithay::wayland::seat::WaylandFocus;
 use smithay::wayland::shell::xdg::{
@@ -19,25 +23,35 @@ use smithay::wayland::shell::xdg::{
     XdgToplevelSurfaceData,
 };
 
-fn get_next_focus_id(layout: &Layout, id: Id, before: bool) -> Option<Id> {
-    let element = layout.get_element(id)?;
-    match element {
-        LayoutElement::Window(w) if !before => Some(w.id),
-        LayoutElement::Window(w) => get_next_focus_id(layout, w.parent, true),
-        LayoutElement::SubLayout(sl) => {
-            let index = match (sl.last_focused, before) {
-                (0, _) => 1,
-                (i, true) => i - 1,
-                (i, false) => i,
-            };
-            if let Some(el) = sl.children.get(index) {
-                get_next_focus_id(layout, *el, false)
-            } else if let Some(p) = sl.parent {
-                get_next_focus_id(layout, p, true)
-            } else {
-                None
-            }
+/// Window to focus once the element `id` goes away: the closest of its siblings, then of the
+/// siblings of its parents, minimized windows being skipped
+pub(crate) fn get_next_focus_id(layout: &Layout, mut id: Id) -> Option<Id> {
+    loop {
+        let parent = layout.get_sublayout(layout.get_parent(id)?)?;
+        let index = parent.children.iter().position(|child| *child == id)?;
+        let next = parent.children[..index]
+            .iter()
+            .rev()
+            .chain(&parent.children[index + 1..])
+            .find_map(|child| get_focusable_window(layout, *child));
+        if next.is_some() {
+            return next;
         }
+        id = parent.id;
+    }
+}
+
+/// A window of the element `id` that isn't minimized, the last focused one first
+fn get_focusable_window(layout: &Layout, id: Id) -> Option<Id> {
+    match layout.get_element(id)? {
+        LayoutElement::Window(w) if w.state().contains(WindowState::MINIMIZED) => None,
+        LayoutElement::Window(w) => Some(w.id),
+        LayoutElement::SubLayout(sl) => sl
+            .children
+            .get(sl.last_focused)
+            .into_iter()
+            .chain(&sl.children)
+            .find_map(|child| get_focusable_window(layout, *child)),
     }
 }
 
@@ -47,21 +61,28 @@ impl XdgShellHandler for PocoWM {
     }
 
     fn new_toplevel(&mut self, surface: ToplevelSurface) {
-        let window = Window::from_surface(surface);
+        let window = Window::from_surface(surface, self.config.clone());
+        // New windows go on the output under the cursor, or the focused one
+        let pointer_output = self.seat.get_pointer().and_then(|pointer| {
+            let location = pointer.current_location();
+            self.renderer.output_under(location).next().cloned()
+        });
+        pointer_output.map(|output| self.activate_output(&output));
         let output_geo = self
             .renderer
-            .outputs()
-            .next()
-            .and_then(|o| self.renderer.output_geometry(o))
+            .layout_output(&self.layout)
+            .and_then(|o| self.renderer.output_geometry(&o))
             .unwrap_or_default();
         *window.floating_rect_mut() = Rectangle::new(
-            (output_geo.size.w / 4, output_geo.size.h / 4).into(),
+            (
+                output_geo.loc.x + output_geo.size.w / 4,
+                output_geo.loc.y + output_geo.size.h / 4,
+            )
+                .into(),
             (output_geo.size.w / 2, output_geo.size.h / 2).into(),
         );
         let id = self
-            .seat
-            .get_keyboard()
-            .and_then(|k| k.current_focus())
+            .focused_window()
             .and_then(|w| self.layout.get_window_id(&w))
             .unwrap_or_default();
         let Some(new_id) = self.layout.insert_window(id, window.clone()) else {
@@ -73,13 +94,24 @@ impl XdgShellHandler for PocoWM {
     }
 
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
-        let window = self
-            .layout
-            .get_window_from_surface(surface.wl_surface())
-            .unwrap();
-        self.renderer.unmap_elem(window);
-        let id = self.layout.get_window_id(window).unwrap();
-        let focus_id = get_next_focus_id(&self.layout, id, true);
+        let Some(window) = self.layout.get_window_from_surface(surface.wl_surface()) else {
+            let Some((id, window)) = self.workspaces.remove_window(surface.wl_surface()) else {
+                return;
+            };
+            self.renderer.unmap_elem(&window);
+            self.forget_minimized(&window);
+            self.close_window_menu(&window);
+            self.render_outputs();
+            self.emit_ipc_event(Event::WindowDestroyed(self.window_info(id, &window)));
+            return;
+        };
+        let window = window.clone();
+        self.renderer.unmap_elem(&window);
+        self.forget_minimized(&window);
+        self.close_window_menu(&window);
+        let id = self.layout.get_window_id(&window).unwrap();
+        let focus_id = get_next_focus_id(&self.layout, id);
+        self.emit_window_event(id, Event::WindowDestroyed);
         self.layout.remove_element(id);
         self.renderer.render(&self.layout);
 
@@ -142,6 +174,33 @@ impl XdgShellHandler for PocoWM {
     fn minimize_request(&mut self, surface: ToplevelSurface) {
         self.xdg_minimize_request(&surface);
     }
+
+    fn fullscreen_request(&mut self, surface: ToplevelSurface, output: Option<WlOutput>) {
+        self.xdg_fullscreen_request(&surface, output);
+    }
+
+    /// Opens the window menu for client side decorations, `location` being relative to the window
+    /// geometry
+    fn show_window_menu(
+        &mut self,
+        surface: ToplevelSurface,
+        _seat: WlSeat,
+        _serial: Serial,
+        location: Point<i32, Logical>,
+    ) {
+        let Some((_, window)) = self.find_window(surface.wl_surface()) else {
+            return;
+        };
+        let Some(geometry) = self.renderer.element_geometry(&window) else {
+            return;
+        };
+        let location = geometry.loc + location + Point::from((0, window.title_height()));
+        self.open_window_menu(&window, location.to_f64());
+    }
+
+    fn unfullscreen_request(&mut self, surface: ToplevelSurface) {
+        self.xdg_unfullscreen_request(&surface);
+    }
 }
 
 fn check_grab(
@@ -167,17 +226,23 @@ impl PocoWM {
         let Ok(root) = find_popup_root_surface(&PopupKind::Xdg(surface.clone())) else {
             return;
         };
-        let Some(window) = self.layout.get_window_from_surface(&root) else {
+        let Some((_, window)) = self.find_window(&root) else {
             return;
         };
 
-        let Some(output) = self.renderer.outputs().next() else {
+        let Some(output) = self
+            .renderer
+            .outputs_for_element(&window)
+            .into_iter()
+            .next()
+            .or_else(|| self.renderer.outputs().next().cloned())
+        else {
             return;
         };
-        let Some(output_geometry) = self.renderer.output_geometry(output) else {
+        let Some(output_geometry) = self.renderer.output_geometry(&output) else {
             return;
         };
-        let Some(window_geometry) = self.renderer.element_geometry(window) else {
+        let Some(window_geometry) = self.renderer.element_geometry(&window) else {
             return;
         };
 
@@ -216,25 +281,26 @@ impl PocoWM {
         {
             return;
         }
-        let Some(window) = self.layout.get_window_from_surface(&wl_surface) else {
+        let Some(window) = self.grabbed_window(wl_surface) else {
             return;
         };
         // if *window.state() != WindowState::FLOATING {
         //     return;
         // }
-        let Some(initial_window_location) = self.renderer.element_location(window) else {
+        let Some(initial_window_location) = self.renderer.element_location(&window) else {
             return;
         };
 
         let grab = MoveGrab {
             start_data,
-            window: window.clone(),
+            window,
             initial_window_location,
             new_location: initial_window_location,
             pointer_location: pointer.current_location(),
         };
 
-        pointer.set_grab(self, grab, serial, Focus::Clear)
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        self.renderer.cursor.grab_icon = Some(CursorIcon::Grabbing);
     }
 
     pub fn xdg_resize_request(
@@ -248,16 +314,28 @@ impl PocoWM {
         let Some(pointer) = seat.get_pointer() else {
             return;
         };
-        let Some(window) = self.layout.get_window_from_surface(wl_surface) else {
+        let Some(start_data) = check_grab(seat, wl_surface, serial) else {
             return;
         };
-        if *window.state() != WindowState::FLOATING {
+        let Some(window) = self.grabbed_window(wl_surface) else {
+            return;
+        };
+        if window.state().is_empty() {
+            let Some(id) = self.layout.get_window_id(&window) else {
+                return;
+            };
+            let splits = [edges.get_horizontal(), edges.get_vertical()]
+                .into_iter()
+                .filter(|edge| !edge.is_empty())
+                .filter_map(|edge| self.layout.get_split(id, edge))
+                .collect::<Vec<_>>();
+            self.tiled_resize_request(start_data, splits, serial);
             return;
         }
-        let Some(start_data) = check_grab(&seat, wl_surface, serial) else {
+        if *window.state() != WindowState::FLOATING {
             return;
-        };
-        let Some(initial_window_location) = self.renderer.element_location(window) else {
+        }
+        let Some(initial_window_location) = self.renderer.element_location(&window) else {
             return;
         };
         let initial_window_size = window.geometry().size;
@@ -277,53 +355,205 @@ impl PocoWM {
 
         let grab = ResizeGrab {
             start_data,
-            window: window.clone(),
+            window,
             initial_rect: initial_window_rect,
             last_window_size: initial_window_rect.size,
             edges: edges.into(),
         };
 
-        pointer.set_grab(self, grab, serial, Focus::Clear)
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        self.renderer.cursor.grab_icon = Some(edges.into());
+    }
+
+    /// Starts moving the splits between the given pairs of tiled siblings with the pointer
+    pub fn tiled_resize_request(
+        &mut self,
+        start_data: GrabStartData<PocoWM>,
+        splits: Vec<(Id, Id)>,
+        serial: Serial,
+    ) {
+        let Some(pointer) = self.seat.get_pointer() else {
+            return;
+        };
+        let splits = splits
+            .into_iter()
+            .filter_map(|(before, after)| SplitDrag::new(self, before, after))
+            .collect::<Vec<_>>();
+        if splits.is_empty() {
+            return;
+        }
+        // Splits across both directions show the cursor moving every way
+        let icon = splits
+            .iter()
+            .map(|split| match split.layout_type {
+                LayoutType::Vertical => CursorIcon::RowResize,
+                _ => CursorIcon::ColResize,
+            })
+            .reduce(|a, b| if a == b { a } else { CursorIcon::Move });
+        let grab = TiledResizeGrab { start_data, splits };
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        self.renderer.cursor.grab_icon = icon;
     }
 
     pub fn xdg_maximize_request(&mut self, surface: &ToplevelSurface) {
-        let Some(window) = self.layout.get_window_from_surface(surface.wl_surface()) else {
+        let Some((id, window)) = self.find_window(surface.wl_surface()) else {
             return;
         };
+        let changed = !window.state().contains(WindowState::MAXIMIZED);
         window.state_mut().insert(WindowState::MAXIMIZED);
-        self.renderer.render(&self.layout);
+        self.render_outputs();
+        changed.then(|| self.emit_window_state(id, &window));
     }
 
     pub fn xdg_unmaximize_request(&mut self, surface: &ToplevelSurface) {
-        let Some(window) = self.layout.get_window_from_surface(surface.wl_surface()) else {
+        let Some((id, window)) = self.find_window(surface.wl_surface()) else {
             return;
         };
+        let changed = window.state().contains(WindowState::MAXIMIZED);
         window.state_mut().remove(WindowState::MAXIMIZED);
-        self.renderer.render(&self.layout);
+        self.render_outputs();
+        changed.then(|| self.emit_window_state(id, &window));
+    }
+
+    /// Makes the window cover its output, or `output` if it is shown on another one. The state it
+    /// had before is kept for when it leaves fullscreen. Windows of the other workspaces stay on
+    /// theirs
+    pub fn xdg_fullscreen_request(&mut self, surface: &ToplevelSurface, output: Option<WlOutput>) {
+        let name = output
+            .as_ref()
+            .and_then(Output::from_resource)
+            .filter(|output| self.layout.output.as_ref() != Some(output))
+            .and_then(|output| self.workspaces.get_on_output(&output))
+            .map(|workspace| workspace.name.clone());
+        let id = self
+            .layout
+            .get_window_from_surface(surface.wl_surface())
+            .and_then(|window| self.layout.get_window_id(window));
+        if let Some((id, name)) = Option::zip(id, name) {
+            self.move_window_to_workspace(id, name.clone());
+            self.switch_workspace(&WorkspaceTarget::Name(name));
+        }
+
+        let Some((id, window)) = self.find_window(surface.wl_surface()) else {
+            return;
+        };
+        let changed = !window.state().contains(WindowState::FULLSCREEN);
+        window.state_mut().insert(WindowState::FULLSCREEN);
+        surface.with_pending_state(|state| {
+            state.states.set(xdg_toplevel::State::Fullscreen);
+            state.fullscreen_output = output;
+        });
+        self.render_outputs();
+        changed.then(|| self.emit_window_state(id, &window));
+    }
+
+    pub fn xdg_unfullscreen_request(&mut self, surface: &ToplevelSurface) {
+        let Some((id, window)) = self.find_window(surface.wl_surface()) else {
+            return;
+        };
+        let changed = window.state().contains(WindowState::FULLSCREEN);
+        window.state_mut().remove(WindowState::FULLSCREEN);
+        surface.with_pending_state(|state| {
+            state.states.unset(xdg_toplevel::State::Fullscreen);
+            state.fullscreen_output = None;
+        });
+        self.render_outputs();
+        changed.then(|| self.emit_window_state(id, &window));
     }
 
     pub fn xdg_minimize_request(&mut self, surface: &ToplevelSurface) {
-        let Some(window) = self.layout.get_window_from_surface(surface.wl_surface()) else {
+        let Some((id, window)) = self.find_window(surface.wl_surface()) else {
             return;
         };
+        if window.state().contains(WindowState::MINIMIZED) {
+            return;
+        }
         window.state_mut().insert(WindowState::MINIMIZED);
+        self.minimized.push(window.clone());
+        // Only a window of the active workspace can have the focus
+        let focused = self.focused_window() == Some(window.clone());
+        self.render_outputs();
+        self.emit_window_state(id, &window);
+        if focused {
+            self.focus_window(get_next_focus_id(&self.layout, id));
+        }
+    }
+
+    /// Window of `surface` for a move or resize, whose workspace becomes the active one when it is
+    /// shown on another output
+    fn grabbed_window(&mut self, surface: &WlSurface) -> Option<Window> {
+        let (_, window) = self.find_window(surface)?;
+        if self.layout.get_window_id(&window).is_none() {
+            self.focus_visible_window(&window);
+        }
+        self.layout.get_window_id(&window).map(|_| window)
+    }
+
+    /// Tells the IPC clients the state of the window `id` of any workspace changed
+    fn emit_window_state(&mut self, id: Id, window: &Window) {
+        let info = self.window_info(id, window);
+        self.emit_ipc_event(Event::WindowState(info));
+    }
+
+    /// Brings a minimized window back on the active workspace and focuses it
+    pub fn unminimize_window(&mut self, window: &Window) {
+        if !window.state().contains(WindowState::MINIMIZED) {
+            return;
+        }
+        self.forget_minimized(window);
+        let id = match self.layout.get_window_id(window) {
+            Some(id) => Some(id),
+            None => {
+                // It was minimized on another workspace
+                let Some((_, window)) = window
+                    .wl_surface()
+                    .and_then(|surface| self.workspaces.remove_window(&surface))
+                else {
+                    return;
+                };
+                let sibling = self
+                    .focused_window()
+                    .and_then(|w| self.layout.get_window_id(&w))
+                    .unwrap_or_default();
+                self.layout.insert_window(sibling, window)
+            }
+        };
+        window.state_mut().remove(WindowState::MINIMIZED);
         self.renderer.render(&self.layout);
+        id.map(|id| self.emit_window_event(id, Event::WindowState));
+        self.focus_window(id);
+    }
+
+    /// Most recently minimized window, among the ones of `app_id` if given
+    pub fn last_minimized(&self, app_id: Option<&str>) -> Option<Window> {
+        self.minimized
+            .iter()
+            .rev()
+            .find(|window| app_id.is_none() || window.app_id().as_deref() == app_id)
+            .cloned()
     }
 }
 
 pub(super) fn handle_commit(state: &mut PocoWM, surface: &WlSurface) {
-    state.layout.get_window_from_surface(surface).map(|window| {
-        with_states(surface, |states| {
-            states
-                .data_map
-                .get::<XdgToplevelSurfaceData>()
-                .and_then(|data| data.lock().ok())
-                .is_some_and(|data| !data.initial_configure_sent)
-        })
-        .then(|| {
-            window.toplevel().map(|t| t.send_configure());
-        });
+    let initial_commit = with_states(surface, |states| {
+        states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .and_then(|data| data.lock().ok())
+            .is_some_and(|data| !data.initial_configure_sent)
     });
+    if let Some(window) = state
+        .layout
+        .get_window_from_surface(surface)
+        .filter(|_| initial_commit)
+        .cloned()
+    {
+        window.toplevel().map(|t| t.send_configure());
+        // Only now has the client set the app_id and title
+        if let Some(id) = state.layout.get_window_id(&window) {
+            state.emit_window_event(id, Event::WindowCreated);
+        }
+    }
 
     state.popups.commit(surface);
     state.popups.find_popup(surface).map(|popup| match popup {
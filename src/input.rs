@@ -1,18 +1,26 @@
-use crate::layout::{Id, LayoutType};
-use crate::utils::Edge;
+use crate::action::Action;
+use crate::focus::FocusTarget;
+use crate::ipc::protocol::Event;
+use crate::layout::Id;
 use crate::window::Window;
 use crate::PocoWM;
+use anyhow::anyhow;
 use bitflags::bitflags;
+use serde::{Deserialize, Deserializer};
 use smithay::backend::input::{
     AbsolutePositionEvent as _, Axis, ButtonState, Event as _, InputBackend, InputEvent, KeyState,
     KeyboardKeyEvent as _, PointerAxisEvent as _, PointerButtonEvent as _,
 };
-use smithay::input::keyboard;
-use smithay::input::pointer;
-use smithay::utils::SERIAL_COUNTER;
+use smithay::desktop::LayerSurface;
+use smithay::input::keyboard::{self, Keysym};
+use smithay::input::pointer::{self, CursorImageStatus};
+use smithay::utils::{Logical, Point, SERIAL_COUNTER};
+use smithay::wayland::shell::wlr_layer::Layer;
+use std::str::FromStr;
 
 bitflags! {
-    struct KeyModifiers: u8 {
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct KeyModifiers: u8 {
         const CTRL = 1;
         const SHIFT = 2;
         const ALT = 4;
@@ -39,15 +47,54 @@ impl From<&keyboard::ModifiersState> for KeyModifiers {
     }
 }
 
+impl FromStr for KeyModifiers {
+    type Err = anyhow::Error;
+
+    /// Parses `+`-separated modifier names, e.g. `Alt+Shift`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('+')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(KeyModifiers::empty(), |modifiers, name| {
+                let modifier = match name.to_lowercase().as_str() {
+                    "ctrl" | "control" => KeyModifiers::CTRL,
+                    "shift" => KeyModifiers::SHIFT,
+                    "alt" | "mod1" => KeyModifiers::ALT,
+                    "super" | "logo" | "mod4" => KeyModifiers::SUPER,
+                    _ => return Err(anyhow!("Unknown modifier: {}", name)),
+                };
+                Ok(modifiers | modifier)
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyModifiers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Key presses kept from the clients
+enum Intercepted {
+    Action(Action),
+    /// Pressed while the minimized windows picker is shown
+    Picker(Keysym),
+    /// Pressed while a window menu is shown
+    Menu(Keysym),
+    /// Pressed with an action bound to its release
+    ReleaseBinding,
+}
+
 impl PocoWM {
-    pub(crate) fn handle_input<B: InputBackend>(&mut self, event: InputEvent<B>) -> Option<()> {
+    pub fn handle_input<B: InputBackend>(&mut self, event: InputEvent<B>) -> Option<()> {
         match event {
             InputEvent::Keyboard { event } => {
                 let serial = SERIAL_COUNTER.next_serial();
                 let time = event.time_msec();
                 let event_state = event.state();
-                self.seat.get_keyboard().map(|keyboard| {
-                    keyboard.input::<(), _>(
+                let intercepted = self.seat.get_keyboard().and_then(|keyboard| {
+                    keyboard.input::<Intercepted, _>(
                         self,
                         event.key_code(),
                         event_state,
@@ -62,119 +109,82 @@ impl PocoWM {
                                     state.pressed_keys.remove(&key.modified_sym());
                                 }
                             }
-                            if event_state != KeyState::Pressed {
-                                return keyboard::FilterResult::Forward;
+                            // The window menu and the picker take every key press while shown
+                            let intercepted = if state.renderer.menu.is_some() {
+                                Some(Intercepted::Menu(key.modified_sym()))
+                            } else if state.renderer.picker.is_some() {
+                                Some(Intercepted::Picker(key.modified_sym()))
+                            } else {
+                                None
+                            };
+                            if let Some(intercepted) = intercepted {
+                                return match event_state {
+                                    KeyState::Pressed => {
+                                        keyboard::FilterResult::Intercept(intercepted)
+                                    }
+                                    KeyState::Released => keyboard::FilterResult::Forward,
+                                };
                             }
                             let modifiers = KeyModifiers::from(modifiers);
-                            if !modifiers.contains(KeyModifiers::ALT) {
-                                return keyboard::FilterResult::Forward;
-                            }
                             let syms = key.raw_syms();
-                            if syms.contains(&keyboard::Keysym::Return) {
-                                let _ = std::process::Command::new("kitty")
-                                    .stdout(std::process::Stdio::null())
-                                    .stderr(std::process::Stdio::null())
-                                    .spawn();
-                                return keyboard::FilterResult::Intercept(());
-                            }
-                            if syms.contains(&keyboard::Keysym::e) {
-                                keyboard
-                                    .current_focus()
-                                    .and_then(|w| if w.state().is_empty() { Some(w) } else { None })
-                                    .and_then(|w| state.layout.get_window_id(&w))
-                                    .and_then(|id| state.layout.get_parent(id))
-                                    .and_then(|id| state.layout.get_element_mut(id))
-                                    .and_then(|e| e.get_sublayout_mut())
-                                    .map(|layout| {
-                                        layout.layout_type = match layout.layout_type {
-                                            LayoutType::Horizontal => LayoutType::Vertical,
-                                            LayoutType::Vertical => LayoutType::Horizontal,
-                                            LayoutType::Tabbed => LayoutType::Horizontal,
-                                        };
-                                    });
-                                state.renderer.render(&state.layout);
-                                return keyboard::FilterResult::Intercept(());
-                            }
-
-                            if syms.contains(&keyboard::Keysym::h) {
-                                keyboard
-                                    .current_focus()
-                                    .and_then(|w| state.layout.get_window_id(&w))
-                                    .and_then(|id| state.layout.get_window_neighbor(id, Edge::LEFT))
-                                    .map(|id| state.focus_window(Some(id)));
-                                return keyboard::FilterResult::Intercept(());
-                            }
-                            if syms.contains(&keyboard::Keysym::j) {
-                                keyboard
-                                    .current_focus()
-                                    .and_then(|w| state.layout.get_window_id(&w))
-                                    .and_then(|id| {
-                                        state.layout.get_window_neighbor(id, Edge::BOTTOM)
-                                    })
-                                    .map(|id| state.focus_window(Some(id)));
-                                return keyboard::FilterResult::Intercept(());
-                            }
-                            if syms.contains(&keyboard::Keysym::k) {
-                                keyboard
-                                    .current_focus()
-                                    .and_then(|w| state.layout.get_window_id(&w))
-                                    .and_then(|id| state.layout.get_window_neighbor(id, Edge::TOP))
-                                    .map(|id| state.focus_window(Some(id)));
-                                return keyboard::FilterResult::Intercept(());
-                            }
-                            if syms.contains(&keyboard::Keysym::l) {
-                                keyboard
-                                    .current_focus()
-                                    .and_then(|w| state.layout.get_window_id(&w))
-                                    .and_then(|id| {
-                                        state.layout.get_window_neighbor(id, Edge::RIGHT)
-                                    })
-                                    .map(|id| state.focus_window(Some(id)));
-                                return keyboard::FilterResult::Intercept(());
-                            }
-
-                            if syms.contains(&keyboard::Keysym::b) {
-                                state.switch_to_layout(LayoutType::Vertical);
-                                return keyboard::FilterResult::Intercept(());
+                            let release = event_state == KeyState::Released;
+                            if let Some(action) = state.bindings.find(modifiers, &syms, release) {
+                                return keyboard::FilterResult::Intercept(Intercepted::Action(
+                                    action.clone(),
+                                ));
                             }
-                            if syms.contains(&keyboard::Keysym::n) {
-                                state.switch_to_layout(LayoutType::Horizontal);
-                                return keyboard::FilterResult::Intercept(());
-                            }
-                            if syms.contains(&keyboard::Keysym::f) {
-                                state.toggle_floating();
-                                return keyboard::FilterResult::Intercept(());
+                            // The client would otherwise see the key pressed but never released
+                            if !release && state.bindings.find(modifiers, &syms, true).is_some() {
+                                return keyboard::FilterResult::Intercept(
+                                    Intercepted::ReleaseBinding,
+                                );
                             }
                             keyboard::FilterResult::Forward
                         },
                     )
                 });
+                match intercepted {
+                    Some(Intercepted::Action(action)) => self.run_action(action),
+                    Some(Intercepted::Picker(keysym)) => self.picker_key(keysym),
+                    Some(Intercepted::Menu(keysym)) => self.menu_key(keysym),
+                    Some(Intercepted::ReleaseBinding) | None => {}
+                }
             }
             InputEvent::PointerMotion { .. } => {}
             InputEvent::PointerMotionAbsolute { event, .. } => {
-                let output = self.renderer.outputs().next()?;
-                let output_geometry = self.renderer.output_geometry(output)?;
-                let pos =
-                    event.position_transformed(output_geometry.size) + output_geometry.loc.to_f64();
+                // Absolute devices span every output
+                let geometry = self
+                    .renderer
+                    .outputs()
+                    .filter_map(|output| self.renderer.output_geometry(output))
+                    .reduce(|a, b| a.merge(b))?;
+                let pos = event.position_transformed(geometry.size) + geometry.loc.to_f64();
+                self.menu_motion(pos);
+                self.renderer.cursor.location = pos;
                 let serial = SERIAL_COUNTER.next_serial();
                 let pointer = self.seat.get_pointer()?;
                 if !pointer.is_grabbed() {
-                    let pointed_window = self.renderer.element_under(pos).map(|(w, _)| w.clone());
-                    let focused_window = self.seat.get_keyboard().and_then(|k| k.current_focus());
+                    let pointed_window = self
+                        .focus_target_under(pos)
+                        .and_then(|(target, _)| target.into_window());
+                    let focused_window = self.focused_window();
                     Option::zip(pointed_window, focused_window).map(
                         |(pointed_window, focused_window)| {
                             if pointed_window != focused_window {
-                                self.focus_window(self.layout.get_window_id(&pointed_window));
+                                self.focus_visible_window(&pointed_window);
                             }
                         },
                     );
                 }
 
+                let under = self.focus_target_under(pos);
+                // Off every surface, the compositor picks the cursor again
+                if under.is_none() {
+                    self.renderer.cursor.status = CursorImageStatus::default_named();
+                }
                 pointer.motion(
                     self,
-                    self.renderer
-                        .element_under(pos)
-                        .map(|(w, p)| (w.clone(), p.to_f64())),
+                    under,
                     &pointer::MotionEvent {
                         location: pos,
                         serial,
@@ -187,6 +197,11 @@ impl PocoWM {
                 let pointer = self.seat.get_pointer()?;
                 let button_state = event.state();
                 let serial = SERIAL_COUNTER.next_serial();
+                // A click anywhere closes the window menu, running the entry under it
+                if button_state == ButtonState::Pressed && self.renderer.menu.is_some() {
+                    self.menu_click(pointer.current_location());
+                    return Some(());
+                }
                 match button_state {
                     ButtonState::Pressed if pointer.is_grabbed() => {
                         self.layout.iter_windows().for_each(|window| {
@@ -196,12 +211,50 @@ impl PocoWM {
                         self.focus_window(None);
                     }
                     ButtonState::Pressed => {
-                        let (window, _location) =
-                            self.renderer.element_under(pointer.current_location())?;
-                        self.focus_window(self.layout.get_window_id(window));
-                        self.renderer.elements().for_each(|window| {
-                            window.toplevel().map(|t| t.send_pending_configure());
-                        });
+                        let location = pointer.current_location();
+                        // Tabs and splits belong to the layout of the output they are on
+                        let output = self.renderer.output_under(location).next().cloned();
+                        output.map(|output| self.focus_output(&output));
+                        const BTN_LEFT: u32 = 0x110;
+                        let split = self
+                            .renderer
+                            .split_under(location)
+                            .copied()
+                            .filter(|_| event.button_code() == BTN_LEFT);
+                        // Layer surfaces above the windows take clicks before the layout
+                        if let Some((layer, _)) =
+                            self.layer_under(self.upper_layers(location), location)
+                        {
+                            self.focus_layer(&layer);
+                        } else if let Some(tab) = self.renderer.tab_under(location) {
+                            let id = self.layout.get_last_focused_window(tab.id);
+                            self.focus_window(id);
+                            return Some(());
+                        } else if let Some((window, _)) = self.renderer.element_under(location) {
+                            let window = window.clone();
+                            self.focus_visible_window(&window);
+                            self.renderer.elements().for_each(|window| {
+                                window.toplevel().map(|t| t.send_pending_configure());
+                            });
+                        } else if let Some(split) = split {
+                            let start_data = pointer::GrabStartData {
+                                focus: None,
+                                button: BTN_LEFT,
+                                location,
+                            };
+                            self.tiled_resize_request(
+                                start_data,
+                                vec![(split.before, split.after)],
+                                serial,
+                            );
+                            return Some(());
+                        } else if let Some((layer, _)) =
+                            self.layer_under(&[Layer::Bottom, Layer::Background], location)
+                        {
+                            self.focus_layer(&layer);
+                        } else {
+                            return Some(());
+                        }
                     }
                     ButtonState::Released => {}
                 }
@@ -253,15 +306,86 @@ impl PocoWM {
         frame
     }
 
+    /// Layer surface or window under `location`, along with its location
+    fn focus_target_under(
+        &self,
+        location: Point<f64, Logical>,
+    ) -> Option<(FocusTarget, Point<f64, Logical>)> {
+        // Nothing below the window menu gets the pointer
+        if self
+            .renderer
+            .menu
+            .as_ref()
+            .is_some_and(|menu| menu.contains(location))
+        {
+            return None;
+        }
+        self.layer_under(self.upper_layers(location), location)
+            .map(|(layer, loc)| (FocusTarget::Layer(layer), loc))
+            .or_else(|| {
+                self.renderer
+                    .element_under(location)
+                    .map(|(window, loc)| (FocusTarget::Window(window.clone()), loc.to_f64()))
+            })
+            .or_else(|| {
+                self.layer_under(&[Layer::Bottom, Layer::Background], location)
+                    .map(|(layer, loc)| (FocusTarget::Layer(layer), loc))
+            })
+    }
+
+    /// Window that has the keyboard, if it isn't on a layer surface
+    pub fn focused_window(&self) -> Option<Window> {
+        self.seat
+            .get_keyboard()
+            .and_then(|k| k.current_focus())
+            .and_then(FocusTarget::into_window)
+    }
+
     pub fn focus_window(&mut self, id: Option<Id>) {
         self.layout.iter_windows().for_each(Window::unfocus);
-        id.map(|id| self.layout.on_focus(id));
+        if id.is_some_and(|id| self.layout.on_focus(id)) {
+            self.renderer.render(&self.layout);
+        }
         let window = id.and_then(|id| self.layout.get_window(id));
         window.map(Window::focus);
         let window = window.cloned();
-        self.seat.get_keyboard().map(|keyboard| {
-            let serial = SERIAL_COUNTER.next_serial();
-            keyboard.set_focus(self, window, serial);
-        });
+        let Some(keyboard) = self.seat.get_keyboard() else {
+            return;
+        };
+        // A layer surface asking for the keyboard exclusively keeps it until it goes away
+        let target = match self.exclusive_layer() {
+            Some(layer) => Some(FocusTarget::Layer(layer)),
+            None => window.map(FocusTarget::Window),
+        };
+        let changed = keyboard.current_focus() != target;
+        let serial = SERIAL_COUNTER.next_serial();
+        keyboard.set_focus(self, target.clone(), serial);
+        if !changed {
+            return;
+        }
+        let window = target.and_then(FocusTarget::into_window);
+        let info = Option::zip(id, window).map(|(id, window)| self.window_info(id, &window));
+        self.emit_ipc_event(Event::Focus(info));
+    }
+
+    /// Gives the keyboard to `layer` if it takes it, unless another layer has it exclusively
+    pub fn focus_layer(&mut self, layer: &LayerSurface) {
+        if !layer.can_receive_keyboard_focus()
+            || self
+                .exclusive_layer()
+                .is_some_and(|exclusive| exclusive != *layer)
+        {
+            return;
+        }
+        let Some(keyboard) = self.seat.get_keyboard() else {
+            return;
+        };
+        let target = Some(FocusTarget::Layer(layer.clone()));
+        if keyboard.current_focus() == target {
+            return;
+        }
+        self.layout.iter_windows().for_each(Window::unfocus);
+        keyboard.set_focus(self, target, SERIAL_COUNTER.next_serial());
+        self.emit_ipc_event(Event::Focus(None));
     }
 }
@@ -0,0 +1,374 @@
+pub mod protocol;
+
+use crate::action::Action;
+use crate::layout::{Id, LayoutElement};
+use crate::window::Window;
+use crate::PocoWM;
+use anyhow::{anyhow, Context as _, Result};
+use protocol::{
+    ContainerInfo, Event, EventType, Node, OutputInfo, Request, Response, WindowInfo, WorkspaceInfo,
+};
+use smithay::output::Output;
+use smithay::reexports::calloop::generic::Generic;
+use smithay::reexports::calloop::{Interest, Mode, PostAction};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::io::{ErrorKind, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+/// Environment variable holding the path of the IPC socket
+pub const SOCKET_ENV: &str = "POCOWM_SOCK";
+
+/// Unread bytes after which a subscriber is considered stuck and disconnected
+const MAX_PENDING: usize = 1 << 20;
+/// Length of a request after which a client that never ends it is disconnected
+const MAX_REQUEST: usize = 1 << 20;
+
+/// Path of the IPC socket of the instance listening on `wayland_display`
+pub fn socket_path(wayland_display: &OsStr) -> Option<PathBuf> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")?;
+    Some(
+        PathBuf::from(runtime_dir)
+            .join(format!("pocowm.{}.sock", wayland_display.to_string_lossy())),
+    )
+}
+
+#[derive(Debug)]
+struct IpcClient {
+    stream: UnixStream,
+    /// Bytes waiting for the client to read them
+    pending: Vec<u8>,
+    /// Event types the client subscribed to, `None` until it sends a subscribe request
+    subscriptions: Option<HashSet<EventType>>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct IpcState {
+    path: Option<PathBuf>,
+    clients: HashMap<usize, IpcClient>,
+    next_client: usize,
+}
+
+impl Drop for IpcState {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl PocoWM {
+    /// Listens for IPC clients next to the wayland socket
+    pub(crate) fn init_ipc(&mut self) -> Result<()> {
+        let path = socket_path(&self.socket_name).context("XDG_RUNTIME_DIR is not set")?;
+        // A previous instance may have been killed before removing it
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Failed to bind {}", path.to_string_lossy()))?;
+        listener
+            .set_nonblocking(true)
+            .context("Failed to init IPC socket")?;
+        self.ipc.path = Some(path);
+
+        self.loop_handle
+            .insert_source(
+                Generic::new(listener, Interest::READ, Mode::Level),
+                |_, listener, state| {
+                    let listener = unsafe { listener.get_mut() };
+                    loop {
+                        match listener.accept() {
+                            Ok((stream, _)) => {
+                                if let Err(err) = state.insert_ipc_client(stream) {
+                                    eprintln!("{:?}", err);
+                                }
+                            }
+                            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                            Err(err) => return Err(err),
+                        }
+                    }
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("Failed to init IPC event source")?;
+        Ok(())
+    }
+
+    pub fn ipc_socket_path(&self) -> Option<&PathBuf> {
+        self.ipc.path.as_ref()
+    }
+
+    fn insert_ipc_client(&mut self, stream: UnixStream) -> Result<()> {
+        stream
+            .set_nonblocking(true)
+            .context("Failed to init IPC client")?;
+        let writer = stream.try_clone().context("Failed to init IPC client")?;
+        let id = self.ipc.next_client;
+        self.ipc.next_client += 1;
+        self.ipc.clients.insert(
+            id,
+            IpcClient {
+                stream: writer,
+                pending: Vec::new(),
+                subscriptions: None,
+            },
+        );
+
+        let mut buffer = Vec::new();
+        self.loop_handle
+            .insert_source(
+                Generic::new(stream, Interest::READ, Mode::Level),
+                move |_, stream, state| {
+                    let stream = unsafe { stream.get_mut() };
+                    let mut chunk = [0; 4096];
+                    let closed = loop {
+                        match stream.read(&mut chunk) {
+                            Ok(0) => break true,
+                            Ok(n) => {
+                                buffer.extend_from_slice(&chunk[..n]);
+                                // The rest is read on the next dispatch, once the lines are handled
+                                if buffer.len() > MAX_REQUEST {
+                                    break false;
+                                }
+                            }
+                            Err(err) if err.kind() == ErrorKind::WouldBlock => break false,
+                            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                            Err(_) => break true,
+                        }
+                    };
+                    while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                        let line = buffer.drain(..=end).collect::<Vec<_>>();
+                        state.handle_ipc_message(id, &line);
+                    }
+                    let too_long = buffer.len() > MAX_REQUEST;
+                    if too_long {
+                        eprintln!(
+                            "IPC client {} sent a request too long, disconnecting it",
+                            id
+                        );
+                    }
+                    if closed || too_long {
+                        state.remove_ipc_client(id);
+                        return Ok(PostAction::Remove);
+                    }
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| anyhow!(err.to_string()))
+            .context("Failed to insert IPC client")?;
+        Ok(())
+    }
+
+    fn handle_ipc_message(&mut self, client: usize, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        if line.trim().is_empty() {
+            return;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => self.handle_ipc_request(client, request),
+            Err(err) => Response::Error(format!("Invalid request: {}", err)),
+        };
+        self.send_ipc(client, &response);
+    }
+
+    fn handle_ipc_request(&mut self, client: usize, request: Request) -> Response {
+        match request {
+            Request::Command { command } => match command.parse::<Action>() {
+                Ok(action) => {
+                    self.run_action(action);
+                    Response::Success
+                }
+                Err(err) => Response::Error(err.to_string()),
+            },
+            Request::GetTree => self
+                .ipc_node(self.layout.root().id)
+                .map_or_else(|| Response::Error("Empty layout".into()), Response::Tree),
+            Request::GetWindows => {
+                let mut windows = self
+                    .layout
+                    .iter_windows()
+                    .filter_map(|w| Some(self.window_info(self.layout.get_window_id(w)?, w)))
+                    .collect::<Vec<_>>();
+                windows.sort_by_key(|w| w.id);
+                Response::Windows(windows)
+            }
+            Request::GetOutputs => Response::Outputs(
+                self.renderer
+                    .outputs()
+                    .map(|output| self.output_info(output))
+                    .collect(),
+            ),
+            Request::GetWorkspaces => Response::Workspaces(
+                self.workspaces
+                    .names()
+                    .into_iter()
+                    .map(|name| {
+                        let layout = match self.workspaces.get(name) {
+                            Some(workspace) => &workspace.layout,
+                            None => &self.layout,
+                        };
+                        WorkspaceInfo {
+                            name: name.into(),
+                            focused: name == self.workspaces.active,
+                            output: layout.output.as_ref().map(Output::name),
+                        }
+                    })
+                    .collect(),
+            ),
+            Request::Subscribe { events } => {
+                let events = if events.is_empty() {
+                    EventType::ALL.into()
+                } else {
+                    events.into_iter().collect()
+                };
+                self.ipc
+                    .clients
+                    .get_mut(&client)
+                    .map(|c| c.subscriptions = Some(events));
+                Response::Success
+            }
+        }
+    }
+
+    /// Queues a message for an IPC client, and sends it right away if the socket allows it
+    fn send_ipc<T: serde::Serialize>(&mut self, client: usize, message: &T) {
+        let Some(ipc_client) = self.ipc.clients.get_mut(&client) else {
+            return;
+        };
+        match serde_json::to_vec(message) {
+            Ok(mut message) => {
+                message.push(b'\n');
+                ipc_client.pending.extend(message);
+            }
+            Err(err) => {
+                eprintln!("{:?}", err);
+                return;
+            }
+        }
+        self.flush_ipc_client(client);
+    }
+
+    fn flush_ipc_client(&mut self, client: usize) {
+        let Some(ipc_client) = self.ipc.clients.get_mut(&client) else {
+            return;
+        };
+        while !ipc_client.pending.is_empty() {
+            match ipc_client.stream.write(&ipc_client.pending) {
+                Ok(n) => {
+                    ipc_client.pending.drain(..n);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    self.remove_ipc_client(client);
+                    return;
+                }
+            }
+        }
+        if ipc_client.pending.len() > MAX_PENDING {
+            eprintln!(
+                "IPC client {} is not reading its events, disconnecting it",
+                client
+            );
+            self.remove_ipc_client(client);
+        }
+    }
+
+    /// Forgets an IPC client, its event source removes itself once the socket is shut down
+    fn remove_ipc_client(&mut self, client: usize) {
+        if let Some(ipc_client) = self.ipc.clients.remove(&client) {
+            let _ = ipc_client.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Sends an event to every client subscribed to its type
+    pub(crate) fn emit_ipc_event(&mut self, event: Event) {
+        let event_type = event.event_type();
+        let clients = self
+            .ipc
+            .clients
+            .iter()
+            .filter(|(_, c)| {
+                c.subscriptions
+                    .as_ref()
+                    .is_some_and(|s| s.contains(&event_type))
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        clients
+            .into_iter()
+            .for_each(|client| self.send_ipc(client, &event));
+    }
+
+    /// Sends a [`Event::WindowCreated`]-like event for the window with the given layout id
+    pub(crate) fn emit_window_event(&mut self, id: Id, f: impl FnOnce(WindowInfo) -> Event) {
+        let Some(window) = self.layout.get_window(id) else {
+            return;
+        };
+        let info = self.window_info(id, window);
+        self.emit_ipc_event(f(info));
+    }
+
+    /// Sends what is left of the messages slow IPC clients didn't read yet
+    pub(crate) fn flush_ipc(&mut self) {
+        let clients = self
+            .ipc
+            .clients
+            .iter()
+            .filter(|(_, c)| !c.pending.is_empty())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        clients
+            .into_iter()
+            .for_each(|client| self.flush_ipc_client(client));
+    }
+
+    fn ipc_node(&self, id: Id) -> Option<Node> {
+        match self.layout.get_element(id)? {
+            LayoutElement::SubLayout(sl) => Some(Node::Container(ContainerInfo {
+                id: id.into(),
+                layout_type: sl.layout_type,
+                last_focused: sl.children.get(sl.last_focused).map(|id| (*id).into()),
+                geometry: self
+                    .renderer
+                    .layout_geometry(&self.layout, id)
+                    .map(Into::into),
+                children: sl
+                    .children
+                    .iter()
+                    .filter_map(|id| self.ipc_node(*id))
+                    .collect(),
+            })),
+            LayoutElement::Window(window) => Some(Node::Window(self.window_info(id, window))),
+        }
+    }
+
+    pub(crate) fn window_info(&self, id: Id, window: &Window) -> WindowInfo {
+        WindowInfo {
+            id: id.into(),
+            app_id: window.app_id(),
+            title: window.title(),
+            state: window
+                .state()
+                .iter_names()
+                .map(|(name, _)| name.to_lowercase())
+                .collect(),
+            focused: window.is_focused(),
+            geometry: self.renderer.element_geometry(window).map(Into::into),
+        }
+    }
+
+    pub(crate) fn output_info(&self, output: &Output) -> OutputInfo {
+        let properties = output.physical_properties();
+        OutputInfo {
+            name: output.name(),
+            make: properties.make,
+            model: properties.model,
+            geometry: self.renderer.output_geometry(output).map(Into::into),
+            refresh: output.current_mode().map(|mode| mode.refresh),
+            scale: output.current_scale().fractional_scale(),
+        }
+    }
+}
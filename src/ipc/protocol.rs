@@ -0,0 +1,161 @@
+//! Messages exchanged over the IPC socket, one JSON object per line
+
+use crate::layout::LayoutType;
+use serde::{Deserialize, Serialize};
+use smithay::utils::{Logical, Rectangle};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Runs an action, written like in the `[[bindings]]` of the config file
+    Command {
+        command: String,
+    },
+    /// Layout tree of the active workspace
+    GetTree,
+    /// Windows of the active workspace
+    GetWindows,
+    GetOutputs,
+    GetWorkspaces,
+    /// Turns the connection into a stream of events, every type of event if `events` is empty
+    Subscribe {
+        #[serde(default)]
+        events: Vec<EventType>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Response {
+    Success,
+    Error(String),
+    Tree(Node),
+    Windows(Vec<WindowInfo>),
+    Outputs(Vec<OutputInfo>),
+    Workspaces(Vec<WorkspaceInfo>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Window,
+    Focus,
+    Layout,
+    WindowState,
+    Output,
+    Workspace,
+}
+
+impl EventType {
+    pub const ALL: [Self; 6] = [
+        Self::Window,
+        Self::Focus,
+        Self::Layout,
+        Self::WindowState,
+        Self::Output,
+        Self::Workspace,
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    WindowCreated(WindowInfo),
+    WindowDestroyed(WindowInfo),
+    /// The newly focused window, if any
+    Focus(Option<WindowInfo>),
+    LayoutType {
+        id: usize,
+        layout_type: LayoutType,
+    },
+    WindowState(WindowInfo),
+    OutputAdded(OutputInfo),
+    OutputChanged(OutputInfo),
+    OutputRemoved(OutputInfo),
+    /// The newly active workspace
+    Workspace {
+        name: String,
+    },
+}
+
+impl Event {
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Self::WindowCreated(_) | Self::WindowDestroyed(_) => EventType::Window,
+            Self::Focus(_) => EventType::Focus,
+            Self::LayoutType { .. } => EventType::Layout,
+            Self::WindowState(_) => EventType::WindowState,
+            Self::OutputAdded(_) | Self::OutputChanged(_) | Self::OutputRemoved(_) => {
+                EventType::Output
+            }
+            Self::Workspace { .. } => EventType::Workspace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl From<Rectangle<i32, Logical>> for Geometry {
+    fn from(rect: Rectangle<i32, Logical>) -> Self {
+        Self {
+            x: rect.loc.x,
+            y: rect.loc.y,
+            width: rect.size.w,
+            height: rect.size.h,
+        }
+    }
+}
+
+/// An element of the layout tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    Container(ContainerInfo),
+    Window(WindowInfo),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    pub id: usize,
+    pub layout_type: LayoutType,
+    /// Id of the child that was focused last
+    pub last_focused: Option<usize>,
+    pub geometry: Option<Geometry>,
+    pub children: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    pub id: usize,
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    /// Lowercase `WindowState` flags, empty for a tiled window
+    pub state: Vec<String>,
+    pub focused: bool,
+    pub geometry: Option<Geometry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    pub geometry: Option<Geometry>,
+    /// Refresh rate in mHz
+    pub refresh: Option<i32>,
+    pub scale: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub focused: bool,
+    /// Output the workspace is shown on
+    pub output: Option<String>,
+}
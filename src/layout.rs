@@ -1,12 +1,17 @@
+use crate::ipc::protocol::Event;
 use crate::utils::Edge;
 use crate::window::{Window as InnerWindow, WindowState};
 use crate::PocoWM;
-use derive_more::Deref;
+use anyhow::anyhow;
+use derive_more::{Deref, Into};
+use serde::{Deserialize, Serialize};
+use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::wayland::seat::WaylandFocus;
 use std::collections::HashMap;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Into)]
 pub struct Id(usize);
 impl Id {
     pub fn next(self) -> Self {
@@ -17,32 +22,68 @@ impl Id {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LayoutType {
     #[default]
     Horizontal,
     Vertical,
     Tabbed,
+    Stacked,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+impl FromStr for LayoutType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(Self::Horizontal),
+            "vertical" => Ok(Self::Vertical),
+            "tabbed" => Ok(Self::Tabbed),
+            "stacked" => Ok(Self::Stacked),
+            _ => Err(anyhow!("Unknown layout type: {}", s)),
+        }
+    }
+}
+
+/// Smallest share of its container a tiled element can be resized to
+pub const MIN_FRACTION: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SubLayout {
     pub id: Id,
     pub parent: Option<Id>,
     pub children: Vec<Id>,
     pub last_focused: usize,
     pub layout_type: LayoutType,
+    /// Share of the parent given to this element, relative to its siblings
+    pub weight: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deref)]
+impl Default for SubLayout {
+    fn default() -> Self {
+        Self {
+            id: Default::default(),
+            parent: Default::default(),
+            children: Default::default(),
+            last_focused: Default::default(),
+            layout_type: Default::default(),
+            weight: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deref)]
 pub struct Window {
     pub id: Id,
     pub parent: Id,
+    /// Share of the parent given to this element, relative to its siblings
+    pub weight: f64,
     #[deref]
     pub inner: InnerWindow,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LayoutElement {
     SubLayout(SubLayout),
     Window(Window),
@@ -78,6 +119,25 @@ impl LayoutElement {
         }
     }
     #[inline]
+    pub fn weight(&self) -> f64 {
+        match self {
+            Self::SubLayout(sl) => sl.weight,
+            Self::Window(w) => w.weight,
+        }
+    }
+    #[inline]
+    pub fn set_weight(&mut self, weight: f64) {
+        match self {
+            Self::SubLayout(sl) => sl.weight = weight,
+            Self::Window(w) => w.weight = weight,
+        }
+    }
+    /// Whether the element takes part in tiling, unlike floating or maximized windows
+    #[inline]
+    pub fn is_tiled(&self) -> bool {
+        self.get_window().is_none_or(|w| w.state().is_empty())
+    }
+    #[inline]
     pub fn get_sublayout(&self) -> Option<&SubLayout> {
         match self {
             Self::SubLayout(sl) => Some(sl),
@@ -107,11 +167,13 @@ impl LayoutElement {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Layout {
     // root: SubLayout,
     next_id: Id,
     elements: HashMap<Id, LayoutElement>,
+    /// Output the layout is shown on, `None` while its workspace is hidden
+    pub output: Option<Output>,
 }
 
 impl Layout {
@@ -120,7 +182,11 @@ impl Layout {
         let mut elements = HashMap::default();
         elements.insert(root_id, LayoutElement::SubLayout(SubLayout::default()));
         let next_id = root_id.next();
-        Self { next_id, elements }
+        Self {
+            next_id,
+            elements,
+            output: None,
+        }
     }
     pub fn root(&self) -> &SubLayout {
         self.elements
@@ -247,10 +313,96 @@ impl Layout {
             LayoutElement::Window(Window {
                 id: self.next_id,
                 parent: Default::default(),
+                weight: 1.0,
                 inner: window,
             }),
         )
     }
+    /// Children of `sl` sharing its area, in order
+    pub fn tiled_children<'a>(
+        &'a self,
+        sl: &'a SubLayout,
+    ) -> impl Iterator<Item = (Id, &'a LayoutElement)> + Clone + 'a {
+        sl.children
+            .iter()
+            .copied()
+            .filter_map(|id| self.get_element(id).map(|el| (id, el)))
+            .filter(|(_, el)| el.is_tiled())
+    }
+    /// Walks up from `id` to the closest container whose layout type matches `f`, returns its
+    /// child containing `id`
+    pub fn get_split_child(&self, mut id: Id, f: impl Fn(LayoutType) -> bool) -> Option<Id> {
+        loop {
+            let parent = self.get_parent(id).and_then(|id| self.get_sublayout(id))?;
+            if f(parent.layout_type) {
+                return Some(id);
+            }
+            id = parent.id;
+        }
+    }
+    /// Walks up from `id` to the closest container split along the axis of `edge` in which the
+    /// element has a tiled sibling on that side, returns both siblings in order
+    pub fn get_split(&self, mut id: Id, edge: Edge) -> Option<(Id, Id)> {
+        let layout_type = if edge.is_horizontal() {
+            LayoutType::Horizontal
+        } else {
+            LayoutType::Vertical
+        };
+        loop {
+            let parent = self.get_parent(id).and_then(|id| self.get_sublayout(id))?;
+            if parent.layout_type == layout_type {
+                let siblings = self
+                    .tiled_children(parent)
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>();
+                let i = siblings.iter().position(|sibling| *sibling == id)?;
+                let split = if edge.intersects(Edge::LEFT | Edge::TOP) {
+                    i.checked_sub(1).map(|j| (siblings[j], id))
+                } else {
+                    siblings.get(i + 1).map(|sibling| (id, *sibling))
+                };
+                if split.is_some() {
+                    return split;
+                }
+            }
+            id = parent.id;
+        }
+    }
+    /// Changes the share of its container `id` takes by `delta`, taking or giving the
+    /// difference from its tiled siblings proportionally
+    pub fn resize_element(&mut self, id: Id, delta: f64) -> Option<()> {
+        let parent = self.get_parent(id).and_then(|id| self.get_sublayout(id))?;
+        let weights = self
+            .tiled_children(parent)
+            .map(|(id, el)| (id, el.weight()))
+            .collect::<Vec<_>>();
+        if weights.len() < 2 {
+            return None;
+        }
+        let total = weights.iter().map(|(_, w)| w).sum::<f64>();
+        let weight = weights.iter().find(|(i, _)| *i == id)?.1;
+        let max = 1.0 - MIN_FRACTION * (weights.len() - 1) as f64;
+        let fraction = (weight / total + delta).clamp(MIN_FRACTION, max);
+        let others = total - weight;
+        weights.into_iter().for_each(|(i, w)| {
+            let new_weight = if i == id {
+                fraction
+            } else {
+                w / others * (1.0 - fraction)
+            };
+            self.get_element_mut(i)
+                .map(|el| el.set_weight(new_weight.max(MIN_FRACTION)));
+        });
+        Some(())
+    }
+    /// Gives every child of `id` the same share of it
+    pub fn equalize(&mut self, id: Id) -> Option<()> {
+        let children = self.get_sublayout(id)?.children.clone();
+        children.into_iter().for_each(|id| {
+            self.get_element_mut(id).map(|el| el.set_weight(1.0));
+        });
+        Some(())
+    }
     #[inline]
     pub fn get_parent(&self, id: Id) -> Option<Id> {
         self.get_element(id).and_then(LayoutElement::parent)
@@ -263,8 +415,10 @@ impl Layout {
         })
     }
     fn is_correct_layout_type(&self, layout_type: LayoutType, edge: Edge) -> bool {
-        return (layout_type == LayoutType::Horizontal && edge.is_horizontal())
-            || (layout_type == LayoutType::Vertical && edge.is_vertical());
+        return (matches!(layout_type, LayoutType::Horizontal | LayoutType::Tabbed)
+            && edge.is_horizontal())
+            || (matches!(layout_type, LayoutType::Vertical | LayoutType::Stacked)
+                && edge.is_vertical());
     }
     pub fn get_window_neighbor(&self, id: Id, edge: Edge) -> Option<Id> {
         let mut sl = self.get_parent(id).and_then(|id| self.get_sublayout(id))?;
@@ -275,53 +429,79 @@ impl Layout {
         }
         let index = sl.children.iter().position(|e| e == &child_id)?;
         let new_index = match edge {
-            Edge::TOP | Edge::LEFT => index.saturating_sub(1),
+            Edge::TOP | Edge::LEFT => index.checked_sub(1)?,
             Edge::BOTTOM | Edge::RIGHT => index + 1,
             _ => return None,
         };
-        let mut id = sl.children.get(new_index).copied()?;
+        let id = sl.children.get(new_index).copied()?;
+        self.get_last_focused_window(id)
+    }
+    /// Follows the last focused children from `id` down to a window
+    pub fn get_last_focused_window(&self, mut id: Id) -> Option<Id> {
         while let Some(sl) = self.get_sublayout(id) {
             id = sl.children.get(sl.last_focused).copied()?;
         }
         Some(id)
     }
-    pub fn on_focus(&mut self, id: Id) {
+    /// Returns whether a tabbed or stacked ancestor switched to another child, so needs a render
+    pub fn on_focus(&mut self, id: Id) -> bool {
         let Some(parent) = self.get_parent(id) else {
-            return;
+            return false;
         };
-        self.get_sublayout_mut(parent).map(|sl| {
-            sl.last_focused = sl
+        let switched = self.get_sublayout_mut(parent).is_some_and(|sl| {
+            let index = sl
                 .children
                 .iter()
                 .position(|i| i == &id)
                 .unwrap_or_default();
+            let switched = sl.last_focused != index
+                && matches!(sl.layout_type, LayoutType::Tabbed | LayoutType::Stacked);
+            sl.last_focused = index;
+            switched
         });
-        self.on_focus(parent);
+        self.on_focus(parent) || switched
     }
 }
 
 impl PocoWM {
     pub fn switch_to_layout(&mut self, layout_type: LayoutType) -> Option<()> {
-        let focused_window = self.seat.get_keyboard().and_then(|k| k.current_focus());
+        let focused_window = self.focused_window();
         if let Some(focused_window) = focused_window {
             if focused_window.state().contains(WindowState::FLOATING) {
                 return None;
             }
             let id = self.layout.get_window_id(&focused_window)?;
             let parent = self.layout.insert_sublayout(id, layout_type)?;
-            let el = self.layout.remove_element(id)?;
+            let mut el = self.layout.remove_element(id)?;
+            // The new container takes the place of the window
+            let weight = el.weight();
+            el.set_weight(1.0);
+            self.layout
+                .get_element_mut(parent)
+                .map(|p| p.set_weight(weight));
             self.layout.insert_element(parent, el);
+            self.emit_ipc_event(Event::LayoutType {
+                id: parent.into(),
+                layout_type,
+            });
         } else {
             self.layout.root_mut().layout_type = layout_type;
+            self.emit_ipc_event(Event::LayoutType {
+                id: self.layout.root().id.into(),
+                layout_type,
+            });
         }
         Some(())
     }
     pub fn toggle_floating(&mut self) {
-        let focused_window = self.seat.get_keyboard().and_then(|k| k.current_focus());
+        let focused_window = self.focused_window();
         let Some(focused_window) = focused_window else {
             return;
         };
         focused_window.state_mut().toggle(WindowState::FLOATING);
         self.renderer.render(&self.layout);
+        self.layout
+            .get_window_id(&focused_window)
+            .map(|id| self.emit_window_event(id, Event::WindowState));
     }
 }
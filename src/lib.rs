@@ -0,0 +1,19 @@
+pub mod action;
+pub mod backends;
+mod bindings;
+pub mod config;
+pub mod focus;
+mod grabs;
+mod handlers;
+mod input;
+pub mod ipc;
+pub mod layout;
+pub mod output;
+pub mod renderer;
+mod state;
+pub mod theme;
+pub mod utils;
+pub mod window;
+pub mod workspace;
+
+pub use state::PocoWM;
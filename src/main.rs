@@ -1,19 +1,31 @@
-mod backends;
-mod grabs;
-mod handlers;
-mod input;
-mod layout;
-mod renderer;
-mod state;
-pub mod utils;
-mod window;
-
-use anyhow::Result;
-pub use state::PocoWM;
+use anyhow::{anyhow, Result};
+use pocowm::backends::headless::HeadlessOptions;
+use pocowm::backends::winit::WinitOptions;
+use pocowm::PocoWM;
 
 fn run() -> Result<()> {
+    let mut headless = None;
+    let mut winit = WinitOptions::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--headless" => headless = Some(HeadlessOptions::default()),
+            arg if arg.starts_with("--headless=") => {
+                headless = Some(HeadlessOptions::parse(&arg["--headless=".len()..])?);
+            }
+            arg if arg.starts_with("--winit-outputs=") => {
+                winit = WinitOptions::parse(&arg["--winit-outputs=".len()..])?;
+            }
+            arg => return Err(anyhow!("Unknown argument: {}", arg)),
+        }
+    }
+
     let mut pocowm = PocoWM::new()?;
-    pocowm.init_winit()?;
+    match headless {
+        Some(options) => {
+            pocowm.init_headless(options)?;
+        }
+        None => pocowm.init_winit(winit)?,
+    }
     pocowm.run()?;
 
     Ok(())
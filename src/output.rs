@@ -0,0 +1,212 @@
+use crate::ipc::protocol::Event;
+use crate::window::Window;
+use crate::PocoWM;
+use smithay::desktop::layer_map_for_output;
+use smithay::desktop::utils::send_frames_surface_tree;
+use smithay::input::pointer::CursorImageStatus;
+use smithay::output::{Mode, Output, Scale};
+use smithay::utils::{Logical, Point, Transform};
+use std::time::Duration;
+
+/// Marks the outputs whose mode and transform are up to the backend, like the winit ones which
+/// follow the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedMode;
+
+/// Changes to an output, from the config or from an output management client. Unset fields are
+/// left as they are
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OutputSettings {
+    pub mode: Option<Mode>,
+    pub transform: Option<Transform>,
+    pub scale: Option<Scale>,
+    pub position: Option<Point<i32, Logical>>,
+}
+
+impl OutputSettings {
+    /// Whether the backend of `output` can apply the settings
+    pub fn supported_by(&self, output: &Output) -> bool {
+        if output.user_data().get::<FixedMode>().is_none() {
+            return true;
+        }
+        self.mode
+            .is_none_or(|mode| output.current_mode() == Some(mode))
+            && self
+                .transform
+                .is_none_or(|transform| output.current_transform() == transform)
+    }
+}
+
+impl PocoWM {
+    /// Applies the first `[[outputs]]` entry matching `output`, returns whether it changed
+    pub fn configure_output(&mut self, output: &Output) -> bool {
+        let Some(config) = self
+            .config
+            .outputs
+            .iter()
+            .find(|config| config.matches(output))
+            .cloned()
+        else {
+            return false;
+        };
+        let fixed = output.user_data().get::<FixedMode>().is_some();
+        let settings = OutputSettings {
+            mode: config.mode.filter(|_| !fixed).map(|mode| Mode {
+                size: mode.size,
+                refresh: mode
+                    .refresh
+                    .or_else(|| output.current_mode().map(|mode| mode.refresh))
+                    .unwrap_or(60_000),
+            }),
+            transform: config.transform.filter(|_| !fixed).map(Transform::from),
+            scale: config.scale.filter(|scale| *scale > 0.0).map(|scale| {
+                if scale.fract() == 0.0 {
+                    Scale::Integer(scale as i32)
+                } else {
+                    Scale::Fractional(scale)
+                }
+            }),
+            position: config
+                .position
+                .map(|position| Point::from((position.x, position.y))),
+        };
+        self.apply_output_settings(output, settings)
+    }
+
+    /// Applies the config to every output, re-rendering the layouts if any of them changed
+    pub fn configure_outputs(&mut self) {
+        let mut changed = Vec::new();
+        for output in self.output_management_state.outputs().to_vec() {
+            if self.configure_output(&output) {
+                changed.push(output);
+            }
+        }
+        if changed.is_empty() {
+            return;
+        }
+        self.render_outputs();
+        changed
+            .iter()
+            .for_each(|output| self.emit_ipc_event(Event::OutputChanged(self.output_info(output))));
+        self.refresh_output_heads();
+    }
+
+    /// Changes the state of `output`, returns whether it changed. Disabled outputs stay out of
+    /// the global space
+    pub fn apply_output_settings(&mut self, output: &Output, settings: OutputSettings) -> bool {
+        let position = settings
+            .position
+            .unwrap_or_else(|| output.current_location());
+        let changed = settings
+            .mode
+            .is_some_and(|mode| output.current_mode() != Some(mode))
+            || settings
+                .transform
+                .is_some_and(|transform| output.current_transform() != transform)
+            || settings.scale.is_some_and(|scale| {
+                output.current_scale().fractional_scale() != scale.fractional_scale()
+            })
+            || output.current_location() != position;
+        settings.mode.map(|mode| output.set_preferred(mode));
+        output.change_current_state(
+            settings.mode,
+            settings.transform,
+            settings.scale,
+            Some(position),
+        );
+        if self.renderer.output_geometry(output).is_some() {
+            self.renderer.map_output(output, position);
+        }
+        if changed {
+            self.renderer.elements().for_each(Window::send_scale);
+        }
+        changed
+    }
+
+    /// Tells the windows and layer surfaces shown on `output` that it was drawn
+    pub fn send_frames(&self, output: &Output) {
+        let time = self.start_time.elapsed();
+        let windows = self
+            .renderer
+            .elements()
+            .filter(|window| self.renderer.outputs_for_element(window).contains(output));
+        windows.for_each(|window| {
+            window.send_frame(output, time, Some(Duration::ZERO), |_, _| {
+                Some(output.clone())
+            })
+        });
+        layer_map_for_output(output).layers().for_each(|layer| {
+            layer.send_frame(output, time, Some(Duration::ZERO), |_, _| {
+                Some(output.clone())
+            })
+        });
+        if let CursorImageStatus::Surface(surface) = &self.renderer.cursor.status {
+            send_frames_surface_tree(surface, output, time, Some(Duration::ZERO), |_, _| {
+                Some(output.clone())
+            });
+        }
+    }
+
+    /// Takes `output` out of the global space. The workspace it showed is kept if it has windows
+    pub fn disable_output(&mut self, output: &Output) {
+        if self.renderer.output_geometry(output).is_none() {
+            return;
+        }
+        let info = self.output_info(output);
+        // Layer surfaces are bound to their output, clients make new ones on the others
+        {
+            let mut map = layer_map_for_output(output);
+            let layers = map.layers().cloned().collect::<Vec<_>>();
+            layers.iter().for_each(|layer| {
+                layer.layer_surface().send_close();
+                map.unmap_layer(layer);
+            });
+        }
+        if self
+            .renderer
+            .picker
+            .as_ref()
+            .is_some_and(|picker| picker.output == *output)
+        {
+            self.renderer.picker = None;
+        }
+        if self
+            .renderer
+            .menu
+            .as_ref()
+            .is_some_and(|menu| menu.output == *output)
+        {
+            self.renderer.menu = None;
+        }
+        if self.layout.output.as_ref() == Some(output) {
+            let other = self
+                .workspaces
+                .visible()
+                .find_map(|workspace| workspace.layout.output.clone());
+            other.map(|other| self.focus_output(&other));
+        }
+        if self.layout.output.as_ref() == Some(output) {
+            self.layout.output = None;
+            self.layout
+                .iter_windows()
+                .for_each(|window| self.renderer.unmap_elem(window));
+        }
+        self.workspaces
+            .remove_output(output)
+            .iter()
+            .for_each(|window| self.renderer.unmap_elem(window));
+        self.renderer.unmap_output(output);
+        self.render_outputs();
+        self.emit_ipc_event(Event::OutputRemoved(info));
+    }
+
+    /// Puts a disabled `output` back in the global space, at its current location
+    pub fn enable_output(&mut self, output: &Output) {
+        if self.renderer.output_geometry(output).is_some() {
+            return;
+        }
+        self.renderer.map_output(output, output.current_location());
+        self.add_output(output);
+        self.emit_ipc_event(Event::OutputAdded(self.output_info(output)));
+    }
+}
@@ -0,0 +1,202 @@
+use super::OutputElement;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::{
+    MemoryRenderBuffer, MemoryRenderBufferRenderElement,
+};
+use smithay::backend::renderer::element::surface::{
+    render_elements_from_surface_tree, WaylandSurfaceRenderElement,
+};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::{ImportAll, ImportMem, Texture};
+use smithay::input::pointer::{CursorIcon, CursorImageStatus, CursorImageSurfaceData};
+use smithay::utils::{Logical, Point, Rectangle, Transform};
+use smithay::wayland::compositor::with_states;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Instant;
+use xcursor::parser::parse_xcursor;
+use xcursor::CursorTheme;
+
+/// Theme used when `XCURSOR_THEME` isn't set
+const DEFAULT_THEME: &str = "default";
+/// Size used when `XCURSOR_SIZE` isn't set, in logical pixels
+const DEFAULT_SIZE: u32 = 24;
+
+/// An image of a cursor from the XCursor theme
+#[derive(Debug, Clone)]
+struct Frame {
+    buffer: MemoryRenderBuffer,
+    hotspot: Point<f64, Logical>,
+    /// Time the frame is shown in animated cursors, in milliseconds
+    delay: u32,
+}
+
+/// The pointer, drawn above everything else
+#[derive(Debug)]
+pub struct Cursor {
+    /// Cursor set by the client under the pointer, or by the compositor elsewhere
+    pub status: CursorImageStatus,
+    /// Cursor of the move or resize going on, shown instead of the one of the client
+    pub grab_icon: Option<CursorIcon>,
+    pub location: Point<f64, Logical>,
+    theme: String,
+    size: u32,
+    /// Frames of the icons loaded so far, by buffer scale, empty when missing from the theme
+    icons: RefCell<HashMap<(CursorIcon, i32), Vec<Frame>>>,
+    start_time: Instant,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        let theme = std::env::var("XCURSOR_THEME")
+            .ok()
+            .filter(|theme| !theme.is_empty())
+            .unwrap_or_else(|| DEFAULT_THEME.into());
+        let size = std::env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(DEFAULT_SIZE);
+        Self {
+            status: CursorImageStatus::default_named(),
+            grab_icon: None,
+            location: Point::default(),
+            theme,
+            size,
+            icons: RefCell::default(),
+            start_time: Instant::now(),
+        }
+    }
+}
+
+impl PartialEq for Cursor {
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status
+            && self.grab_icon == other.grab_icon
+            && self.location == other.location
+    }
+}
+
+impl Cursor {
+    /// Icon drawn from the theme, `None` when the cursor is hidden or drawn by the client
+    pub fn icon(&self) -> Option<CursorIcon> {
+        match (self.grab_icon, &self.status) {
+            (Some(icon), _) => Some(icon),
+            (None, CursorImageStatus::Named(icon)) => Some(*icon),
+            (None, _) => None,
+        }
+    }
+
+    /// Loads the frames of `icon` at the size closest to the one asked for, at `buffer_scale`
+    fn load_icon(&self, icon: CursorIcon, buffer_scale: i32) -> Vec<Frame> {
+        let size = self.size * buffer_scale as u32;
+        let theme = CursorTheme::load(&self.theme);
+        let images = std::iter::once(icon.name())
+            .chain(icon.alt_names().iter().copied())
+            .find_map(|name| theme.load_icon(name))
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|data| parse_xcursor(&data))
+            .unwrap_or_default();
+        let Some(nominal) = images
+            .iter()
+            .map(|image| image.size)
+            .min_by_key(|nominal| nominal.abs_diff(size))
+        else {
+            eprintln!("Cursor {} not found in theme {}", icon.name(), self.theme);
+            return Vec::new();
+        };
+        images
+            .into_iter()
+            .filter(|image| image.size == nominal)
+            .map(|image| Frame {
+                buffer: MemoryRenderBuffer::from_slice(
+                    &image.pixels_rgba,
+                    Fourcc::Abgr8888,
+                    (image.width as i32, image.height as i32),
+                    buffer_scale,
+                    Transform::Normal,
+                    None,
+                ),
+                hotspot: Point::from((image.xhot as f64, image.yhot as f64))
+                    .downscale(buffer_scale as f64),
+                delay: image.delay,
+            })
+            .collect()
+    }
+
+    /// Frame of `icon` to show now, the default icon standing in for the ones the theme lacks
+    fn frame(&self, icon: CursorIcon, buffer_scale: i32) -> Option<Frame> {
+        let frames = self
+            .icons
+            .borrow_mut()
+            .entry((icon, buffer_scale))
+            .or_insert_with(|| self.load_icon(icon, buffer_scale))
+            .clone();
+        if frames.is_empty() {
+            return (icon != CursorIcon::Default)
+                .then(|| self.frame(CursorIcon::Default, buffer_scale))
+                .flatten();
+        }
+        let total = frames.iter().map(|frame| frame.delay).sum::<u32>();
+        if total == 0 {
+            return frames.into_iter().next();
+        }
+        let mut time = (self.start_time.elapsed().as_millis() % total as u128) as u32;
+        frames.into_iter().find(|frame| {
+            let shown = time < frame.delay;
+            time = time.saturating_sub(frame.delay);
+            shown
+        })
+    }
+
+    /// Render elements of the cursor if it is on the output of `output_geometry`
+    pub fn render_elements<R>(
+        &self,
+        renderer: &mut R,
+        output_geometry: Rectangle<i32, Logical>,
+        scale: f64,
+    ) -> Vec<OutputElement<R>>
+    where
+        R: smithay::backend::renderer::Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + Texture + 'static,
+    {
+        if !output_geometry.to_f64().contains(self.location) {
+            return Vec::new();
+        }
+        let location = self.location - output_geometry.loc.to_f64();
+        if let (None, CursorImageStatus::Surface(surface)) = (self.grab_icon, &self.status) {
+            let hotspot = with_states(surface, |states| {
+                states
+                    .data_map
+                    .get::<CursorImageSurfaceData>()
+                    .map_or_else(Default::default, |data| data.lock().unwrap().hotspot)
+            });
+            let elements: Vec<WaylandSurfaceRenderElement<R>> = render_elements_from_surface_tree(
+                renderer,
+                surface,
+                (location - hotspot.to_f64()).to_physical_precise_round(scale),
+                scale,
+                1.0,
+                Kind::Cursor,
+            );
+            return elements.into_iter().map(OutputElement::Surface).collect();
+        }
+        let buffer_scale = scale.ceil().max(1.0) as i32;
+        let Some(frame) = self.icon().and_then(|icon| self.frame(icon, buffer_scale)) else {
+            return Vec::new();
+        };
+        MemoryRenderBufferRenderElement::from_buffer(
+            renderer,
+            (location - frame.hotspot).to_physical(scale),
+            &frame.buffer,
+            None,
+            None,
+            None,
+            Kind::Cursor,
+        )
+        .map_err(|err| eprintln!("{:?}", err))
+        .ok()
+        .map(OutputElement::Memory)
+        .into_iter()
+        .collect()
+    }
+}
@@ -0,0 +1,300 @@
+use super::OutputElement;
+use crate::action::Action;
+use crate::theme::Theme;
+use crate::utils::text;
+use crate::window::{Window, WindowState};
+use crate::PocoWM;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::{
+    MemoryRenderBuffer, MemoryRenderBufferRenderElement,
+};
+use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::{ImportAll, ImportMem, Texture};
+use smithay::input::keyboard::Keysym;
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle, Size, Transform};
+use std::cell::RefCell;
+
+/// Width of the window menu
+const MENU_WIDTH: i32 = 160;
+/// Space left of the labels
+const PADDING: i32 = 8;
+
+/// Menu of a window, opened from its title bar or by its client, whose entries run an action on it
+#[derive(Debug, Clone)]
+pub struct WindowMenu {
+    pub output: Output,
+    pub window: Window,
+    /// Labels along with the action they run
+    pub entries: Vec<(String, Action)>,
+    /// Entry under the pointer or chosen with the keyboard
+    pub selected: Option<usize>,
+    /// Relative to the output
+    rect: Rectangle<i32, Logical>,
+    output_loc: Point<i32, Logical>,
+    theme: Theme,
+    background: SolidColorBuffer,
+    highlight: SolidColorBuffer,
+    /// Labels rasterized at the scale they were last drawn at
+    labels: RefCell<Option<(i32, Vec<MemoryRenderBuffer>)>>,
+}
+
+impl PartialEq for WindowMenu {
+    fn eq(&self, other: &Self) -> bool {
+        self.output == other.output
+            && self.window == other.window
+            && self.entries == other.entries
+            && self.selected == other.selected
+            && self.rect == other.rect
+    }
+}
+
+impl WindowMenu {
+    /// Opens the menu of `window` with its top left corner at `location`, moved to stay on the
+    /// output
+    pub fn new(
+        output: Output,
+        output_geometry: Rectangle<i32, Logical>,
+        window: Window,
+        location: Point<i32, Logical>,
+    ) -> Self {
+        let theme = window.config().theme().clone();
+        let entries = Self::entries(&window);
+        let size = Size::from((MENU_WIDTH, entries.len() as i32 * theme.title_height as i32));
+        let location = location - output_geometry.loc;
+        let loc = Point::from((
+            location.x.min(output_geometry.size.w - size.w).max(0),
+            location.y.min(output_geometry.size.h - size.h).max(0),
+        ));
+        Self {
+            output,
+            window,
+            entries,
+            selected: None,
+            rect: Rectangle::new(loc, size),
+            output_loc: output_geometry.loc,
+            background: SolidColorBuffer::new(size, theme.unfocused.background.0),
+            highlight: SolidColorBuffer::new(
+                (MENU_WIDTH, theme.title_height as i32),
+                theme.focused.background.0,
+            ),
+            theme,
+            labels: RefCell::new(None),
+        }
+    }
+
+    /// Entries for the current state of `window`
+    fn entries(window: &Window) -> Vec<(String, Action)> {
+        let state = window.state().clone();
+        let floating = if state.contains(WindowState::FLOATING) {
+            "Tile"
+        } else {
+            "Float"
+        };
+        let maximize = if state.contains(WindowState::MAXIMIZED) {
+            "Restore"
+        } else {
+            "Maximize"
+        };
+        let decorations = if window.is_decorated() {
+            "Hide title bar"
+        } else {
+            "Show title bar"
+        };
+        [
+            (floating, Action::ToggleFloating),
+            (maximize, Action::ToggleMaximize),
+            (decorations, Action::ToggleDecorations),
+            ("Minimize", Action::Minimize),
+            ("Close", Action::Close),
+            ("Kill", Action::Kill),
+        ]
+        .into_iter()
+        .map(|(label, action)| (label.to_owned(), action))
+        .collect()
+    }
+
+    /// Whether `location`, in global coordinates, is on the menu
+    pub fn contains(&self, location: Point<f64, Logical>) -> bool {
+        self.entry_under(location).is_some()
+    }
+
+    /// Index of the entry under `location`, in global coordinates
+    pub fn entry_under(&self, location: Point<f64, Logical>) -> Option<usize> {
+        let location = location - self.output_loc.to_f64();
+        if !self.rect.to_f64().contains(location) {
+            return None;
+        }
+        let index = (location.y - self.rect.loc.y as f64) / self.theme.title_height as f64;
+        Some((index as usize).min(self.entries.len() - 1))
+    }
+
+    /// Moves the selection by `delta` entries, wrapping around
+    pub fn select(&mut self, delta: isize) {
+        let len = self.entries.len() as isize;
+        let current = self
+            .selected
+            .map_or(if delta > 0 { -1 } else { 0 }, |i| i as isize);
+        self.selected = Some((current + delta).rem_euclid(len) as usize);
+    }
+
+    /// Labels rasterized at the scale of the output rounded up, drawn again when it changes
+    fn labels(&self, scale: f64) -> Vec<MemoryRenderBuffer> {
+        let buffer_scale = scale.ceil().max(1.0) as i32;
+        let mut labels = self.labels.borrow_mut();
+        if let Some((_, buffers)) = labels.as_ref().filter(|(old, _)| *old == buffer_scale) {
+            return buffers.clone();
+        }
+        let Some(font) = text::font(&self.theme.font) else {
+            return Vec::new();
+        };
+        let size = Size::from((MENU_WIDTH - 2 * PADDING, self.theme.title_height as i32))
+            .to_buffer(buffer_scale, Transform::Normal);
+        let buffers = self
+            .entries
+            .iter()
+            .map(|(label, _)| {
+                let pixels = text::render_text(
+                    &font,
+                    label,
+                    self.theme.font_size * buffer_scale as f32,
+                    self.theme.focused.title.0,
+                    size,
+                );
+                MemoryRenderBuffer::from_slice(
+                    &pixels,
+                    Fourcc::Argb8888,
+                    size,
+                    buffer_scale,
+                    Transform::Normal,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+        *labels = Some((buffer_scale, buffers.clone()));
+        buffers
+    }
+
+    /// Render elements of the menu, front to back
+    pub fn render_elements<R>(&self, renderer: &mut R, scale: f64) -> Vec<OutputElement<R>>
+    where
+        R: smithay::backend::renderer::Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + Texture + 'static,
+    {
+        let height = self.theme.title_height as i32;
+        let entry_loc = |i: usize| self.rect.loc + Point::from((0, i as i32 * height));
+        let mut elements = self
+            .labels(scale)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, buffer)| {
+                let loc = entry_loc(i) + Point::from((PADDING, 0));
+                MemoryRenderBufferRenderElement::from_buffer(
+                    renderer,
+                    loc.to_physical_precise_round::<_, i32>(scale).to_f64(),
+                    buffer,
+                    None,
+                    None,
+                    None,
+                    Kind::Unspecified,
+                )
+                .map_err(|err| eprintln!("{:?}", err))
+                .ok()
+            })
+            .map(OutputElement::Memory)
+            .collect::<Vec<_>>();
+        if let Some(selected) = self.selected {
+            elements.push(OutputElement::Solid(SolidColorRenderElement::from_buffer(
+                &self.highlight,
+                entry_loc(selected).to_physical_precise_round(scale),
+                scale,
+                1.0,
+                Kind::Unspecified,
+            )));
+        }
+        elements.push(OutputElement::Solid(SolidColorRenderElement::from_buffer(
+            &self.background,
+            self.rect.loc.to_physical_precise_round(scale),
+            scale,
+            1.0,
+            Kind::Unspecified,
+        )));
+        elements
+    }
+}
+
+impl PocoWM {
+    /// Opens the menu of `window` at `location`, in global coordinates
+    pub fn open_window_menu(&mut self, window: &Window, location: Point<f64, Logical>) {
+        let Some(output) = self.renderer.output_under(location).next().cloned() else {
+            return;
+        };
+        let Some(geometry) = self.renderer.output_geometry(&output) else {
+            return;
+        };
+        let location = location.to_i32_round();
+        self.renderer.menu = Some(WindowMenu::new(output, geometry, window.clone(), location));
+    }
+
+    /// Closes the menu if it was opened for `window`
+    pub(crate) fn close_window_menu(&mut self, window: &Window) {
+        if self
+            .renderer
+            .menu
+            .as_ref()
+            .is_some_and(|menu| menu.window == *window)
+        {
+            self.renderer.menu = None;
+        }
+    }
+
+    /// Highlights the entry under the pointer
+    pub fn menu_motion(&mut self, location: Point<f64, Logical>) {
+        let Some(menu) = &mut self.renderer.menu else {
+            return;
+        };
+        menu.selected = menu.entry_under(location);
+    }
+
+    /// Runs the entry under a click, any click closing the menu
+    pub fn menu_click(&mut self, location: Point<f64, Logical>) {
+        let Some(menu) = self.renderer.menu.take() else {
+            return;
+        };
+        menu.entry_under(location)
+            .map(|index| self.run_menu_entry(&menu, index));
+    }
+
+    /// Handles a key pressed while the menu is shown: arrows and jk move the selection, Return
+    /// runs the selected entry and Escape closes the menu
+    pub fn menu_key(&mut self, keysym: Keysym) {
+        let Some(menu) = &mut self.renderer.menu else {
+            return;
+        };
+        match keysym {
+            Keysym::Up | Keysym::k | Keysym::ISO_Left_Tab => menu.select(-1),
+            Keysym::Down | Keysym::j | Keysym::Tab => menu.select(1),
+            Keysym::Return | Keysym::KP_Enter | Keysym::space => {
+                let Some(menu) = self.renderer.menu.take() else {
+                    return;
+                };
+                menu.selected.map(|index| self.run_menu_entry(&menu, index));
+            }
+            Keysym::Escape => self.renderer.menu = None,
+            _ => {}
+        }
+    }
+
+    /// Focuses the window of the menu, then runs the action of its entry `index` on it
+    fn run_menu_entry(&mut self, menu: &WindowMenu, index: usize) {
+        let Some((_, action)) = menu.entries.get(index) else {
+            return;
+        };
+        self.focus_visible_window(&menu.window);
+        if self.focused_window().as_ref() != Some(&menu.window) {
+            return;
+        }
+        self.run_action(action.clone());
+    }
+}
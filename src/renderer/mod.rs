@@ -1,78 +1,396 @@
 // https://danyspin97.org/talks/writing-a-wayland-wallpaper-daemon-in-rust/#47
-use crate::layout::{Layout, LayoutElement, LayoutType, SubLayout};
+pub mod cursor;
+pub mod menu;
+pub mod picker;
+pub mod tabs;
+
+use crate::config::Config;
+use crate::layout::{Id, Layout, LayoutElement, LayoutType, SubLayout};
+use crate::window::render::WindowElement;
 use crate::window::{Window, WindowState};
-use smithay::desktop::Space;
-use smithay::utils::{Logical, Rectangle};
+use cursor::Cursor;
+use menu::WindowMenu;
+use picker::Picker;
+use smithay::backend::renderer::element::memory::MemoryRenderBufferRenderElement;
+use smithay::backend::renderer::element::solid::SolidColorRenderElement;
+use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
+use smithay::backend::renderer::element::utils::RescaleRenderElement;
+use smithay::backend::renderer::element::AsRenderElements as _;
+use smithay::backend::renderer::{ImportAll, ImportMem, Texture};
+use smithay::desktop::{layer_map_for_output, LayerMap, Space};
+use smithay::output::Output;
+use smithay::render_elements;
+use smithay::utils::{Logical, Point, Rectangle, Scale};
+use smithay::wayland::shell::wlr_layer::Layer;
+use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
-
-const GAP: i32 = 20;
+use std::rc::Rc;
+use tabs::{Tab, TabBuffers};
 
 #[derive(Debug, Default, PartialEq)]
 pub struct Renderer {
     pub space: Space<Window>,
+    pub(crate) config: Rc<Config>,
+    /// Area given to each sublayout by the last render, by output name as ids are per layout
+    pub(crate) layout_geometries: HashMap<(String, Id), Rectangle<i32, Logical>>,
+    /// Tabs of every visible tabbed or stacked container
+    pub(crate) tabs: Vec<Tab>,
+    pub(crate) tab_buffers: TabBuffers,
+    /// Gaps between tiled siblings, which can be dragged to resize them
+    pub(crate) splits: Vec<Split>,
+    /// Minimized windows being picked from with the keyboard
+    pub picker: Option<Picker>,
+    /// Window menu opened from a title bar or by a client
+    pub menu: Option<WindowMenu>,
+    pub cursor: Cursor,
+}
+
+/// The gap between two tiled siblings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    pub before: Id,
+    pub after: Id,
+    pub rect: Rectangle<i32, Logical>,
 }
 
 impl Renderer {
+    /// Output `layout` is shown on, the first one if it wasn't given any
+    pub fn layout_output(&self, layout: &Layout) -> Option<Output> {
+        layout
+            .output
+            .clone()
+            .or_else(|| self.space.outputs().next().cloned())
+    }
+
     pub fn render(&mut self, layout: &Layout) -> Option<()> {
-        let output = self.space.outputs().next()?;
-        let full_rect = self.space.output_geometry(output)?;
-        let mut rect = full_rect;
-        rect.loc.x += GAP;
-        rect.loc.y += GAP;
-        rect.size.w -= GAP * 2;
-        rect.size.h -= GAP * 2;
-        self.render_rec(&layout, layout.root(), rect)?;
+        let output = self.layout_output(layout)?;
+        let full_rect = self.space.output_geometry(&output)?;
+        // Tiled and maximized windows stay out of the exclusive zones of layer surfaces
+        let usable_rect = {
+            let mut map = layer_map_for_output(&output);
+            map.arrange();
+            let zone = map.non_exclusive_zone();
+            Rectangle::new(full_rect.loc + zone.loc, zone.size)
+        };
+        let gap = self.config.general.gap;
+        let mut rect = usable_rect;
+        rect.loc.x += gap;
+        rect.loc.y += gap;
+        rect.size.w -= gap * 2;
+        rect.size.h -= gap * 2;
+        self.layout_geometries
+            .retain(|(name, _), _| *name != output.name());
+        self.tabs.retain(|tab| !full_rect.contains(tab.rect.loc));
+        self.splits
+            .retain(|split| !full_rect.contains(split.rect.loc));
+        self.render_rec(&output, layout, layout.root(), rect)?;
+        self.tab_buffers.retain(&output, |id| {
+            layout.get_sublayout(id).is_some_and(|sl| {
+                matches!(sl.layout_type, LayoutType::Tabbed | LayoutType::Stacked)
+            })
+        });
+        let mut fullscreen = false;
         layout.iter_windows().for_each(|window| {
             if window.state().contains(WindowState::MINIMIZED) {
                 self.unmap_elem(window);
-            } else if window.state().contains(WindowState::MAXIMIZED) {
+            } else if window.state().contains(WindowState::FULLSCREEN) {
+                fullscreen = true;
                 self.render_window(window, full_rect);
+                // Raised above the floating windows
+                self.space.raise_element(window, false);
+            } else if window.state().contains(WindowState::MAXIMIZED) {
+                self.render_window(window, usable_rect);
             } else if window.state().contains(WindowState::FLOATING) {
                 self.render_window(window, *window.floating_rect());
             }
         });
+        if fullscreen {
+            // Nothing of the tiling shows under a fullscreen window
+            self.tabs.retain(|tab| !full_rect.contains(tab.rect.loc));
+            self.splits
+                .retain(|split| !full_rect.contains(split.rect.loc));
+        }
+        // Windows learn the outputs they are on, with their scale, without waiting for a frame
+        self.space.refresh();
         Some(())
     }
 
     fn render_rec(
         &mut self,
+        output: &Output,
         layout: &Layout,
         sl: &SubLayout,
         rect: Rectangle<i32, Logical>,
     ) -> Option<()> {
-        let elements = sl
-            .children
-            .iter()
-            .copied()
-            .filter_map(|id| layout.get_element(id))
-            .filter(|el| el.get_window().is_none_or(|w| w.state().is_empty()));
+        self.layout_geometries.insert((output.name(), sl.id), rect);
+        let elements = layout.tiled_children(sl);
+        if matches!(sl.layout_type, LayoutType::Tabbed | LayoutType::Stacked) {
+            return self.render_tabbed(output, layout, sl, elements.collect(), rect);
+        }
         let elements_count = elements.clone().count() as i32;
-        elements.enumerate().try_for_each(|(i, element)| {
+        let total_weight = elements.clone().map(|(_, el)| el.weight()).sum::<f64>();
+        let gap = self.config.general.gap;
+        let available = match sl.layout_type {
+            LayoutType::Vertical => rect.size.h,
+            _ => rect.size.w,
+        } - gap * (elements_count - 1);
+        let mut weight = 0.0;
+        let mut start = 0;
+        let mut previous = None;
+        elements.enumerate().try_for_each(|(i, (id, element))| {
             let i = i as i32;
+            if let Some(before) = previous.replace(id) {
+                let gap_start = start + gap * (i - 1);
+                let rect = match sl.layout_type {
+                    LayoutType::Vertical => Rectangle::new(
+                        (rect.loc.x, rect.loc.y + gap_start).into(),
+                        (rect.size.w, gap).into(),
+                    ),
+                    _ => Rectangle::new(
+                        (rect.loc.x + gap_start, rect.loc.y).into(),
+                        (gap, rect.size.h).into(),
+                    ),
+                };
+                self.splits.push(Split {
+                    before,
+                    after: id,
+                    rect,
+                });
+            }
+            weight += element.weight();
+            // The last element takes what rounding left
+            let end = if i == elements_count - 1 {
+                available
+            } else {
+                (available as f64 * weight / total_weight).round() as i32
+            };
             let mut rect = rect.clone();
             match sl.layout_type {
                 LayoutType::Horizontal => {
-                    rect.size.w = (rect.size.w - GAP * (elements_count - 1)) / elements_count;
-                    // rect.size.w = (rect.size.w + GAP) / elements_count - GAP;
-                    rect.loc.x += (rect.size.w + GAP) * i;
+                    rect.size.w = end - start;
+                    rect.loc.x += start + gap * i;
                 }
                 LayoutType::Vertical => {
-                    rect.size.h = (rect.size.h - GAP * (elements_count - 1)) / elements_count;
-                    // rect.size.h = (rect.size.h + GAP) / elements_count - GAP;
-                    rect.loc.y += (rect.size.h + GAP) * i;
+                    rect.size.h = end - start;
+                    rect.loc.y += start + gap * i;
                 }
-                LayoutType::Tabbed => {}
+                LayoutType::Tabbed | LayoutType::Stacked => {}
             }
+            start = end;
+            self.render_element(output, layout, element, rect)
+        });
 
-            match element {
-                LayoutElement::Window(window) => self.render_window(window, rect),
-                LayoutElement::SubLayout(sl) => self.render_rec(layout, sl, rect),
-            }
+        Some(())
+    }
+
+    /// Draws a tab per child at the top of `rect`, side by side when tabbed and in rows when
+    /// stacked, and only shows the last focused child below them
+    fn render_tabbed(
+        &mut self,
+        output: &Output,
+        layout: &Layout,
+        sl: &SubLayout,
+        elements: Vec<(Id, &LayoutElement)>,
+        rect: Rectangle<i32, Logical>,
+    ) -> Option<()> {
+        let first = elements.first()?.0;
+        let active = sl
+            .children
+            .get(sl.last_focused)
+            .copied()
+            .filter(|id| elements.iter().any(|(i, _)| i == id))
+            .unwrap_or(first);
+        let count = elements.len() as i32;
+        let height = self.config.tabs.height;
+        let bar_height = match sl.layout_type {
+            LayoutType::Stacked => height * count,
+            _ => height,
+        }
+        .min(rect.size.h);
+        let width = rect.size.w / count;
+        let buffers = self.tab_buffers.get(output, sl.id, elements.len());
+        elements.iter().enumerate().for_each(|(i, (id, _))| {
+            let i = i as i32;
+            let tab_rect = match sl.layout_type {
+                LayoutType::Stacked => Rectangle::new(
+                    (rect.loc.x, rect.loc.y + height * i).into(),
+                    (rect.size.w, height).into(),
+                ),
+                _ if i == count - 1 => Rectangle::new(
+                    (rect.loc.x + width * i, rect.loc.y).into(),
+                    (rect.size.w - width * i, height).into(),
+                ),
+                _ => Rectangle::new(
+                    (rect.loc.x + width * i, rect.loc.y).into(),
+                    (width, height).into(),
+                ),
+            };
+            let color = if *id == active {
+                self.config.tabs.active
+            } else {
+                self.config.tabs.inactive
+            };
+            self.tabs.push(Tab::new(
+                *id,
+                tab_rect,
+                sl.layout_type,
+                *id == active,
+                color.0,
+                &mut buffers[i as usize],
+            ));
         });
 
+        let mut content = rect;
+        content.loc.y += bar_height;
+        content.size.h -= bar_height;
+        elements.into_iter().for_each(|(id, element)| {
+            if id == active {
+                self.render_element(output, layout, element, content);
+            } else {
+                self.unmap_rec(layout, element);
+            }
+        });
         Some(())
     }
 
+    fn render_element(
+        &mut self,
+        output: &Output,
+        layout: &Layout,
+        element: &LayoutElement,
+        rect: Rectangle<i32, Logical>,
+    ) -> Option<()> {
+        match element {
+            LayoutElement::Window(window) => self.render_window(window, rect),
+            LayoutElement::SubLayout(sl) => self.render_rec(output, layout, sl, rect),
+        }
+    }
+
+    /// Unmaps every window under `element`, e.g. in a hidden tab
+    fn unmap_rec(&mut self, layout: &Layout, element: &LayoutElement) {
+        match element {
+            LayoutElement::Window(window) => self.space.unmap_elem(window),
+            LayoutElement::SubLayout(sl) => sl
+                .children
+                .iter()
+                .filter_map(|id| layout.get_element(*id))
+                .for_each(|el| self.unmap_rec(layout, el)),
+        }
+    }
+
+    /// Render elements of the tab bars shown on `output`
+    pub fn tab_elements(&self, output: &Output) -> Vec<SolidColorRenderElement> {
+        let Some(output_geometry) = self.space.output_geometry(output) else {
+            return Vec::new();
+        };
+        let scale = Scale::from(output.current_scale().fractional_scale());
+        self.tabs
+            .iter()
+            .filter(|tab| tab.rect.overlaps(output_geometry))
+            .map(|tab| tab.render_element(output_geometry.loc, scale))
+            .collect()
+    }
+
+    /// Fullscreen window shown on `output`, if any
+    pub fn fullscreen_window(&self, output: &Output) -> Option<&Window> {
+        let output_geometry = self.space.output_geometry(output)?;
+        self.space.elements().rev().find(|window| {
+            window.state().contains(WindowState::FULLSCREEN)
+                && self
+                    .space
+                    .element_geometry(window)
+                    .is_some_and(|geometry| geometry.overlaps(output_geometry))
+        })
+    }
+
+    /// Render elements of `output`, front to back: the cursor, the window menu, the picker, the
+    /// overlay layer surfaces, then a fullscreen window or the top layer surfaces, tabs and
+    /// windows, then the bottom and background layer surfaces
+    pub fn output_elements<R>(&self, renderer: &mut R, output: &Output) -> Vec<OutputElement<R>>
+    where
+        R: smithay::backend::renderer::Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + Texture + 'static,
+    {
+        let Some(output_geometry) = self.space.output_geometry(output) else {
+            return Vec::new();
+        };
+        let scale = output.current_scale().fractional_scale();
+        let layer_map = layer_map_for_output(output);
+        let mut elements = self
+            .cursor
+            .render_elements(renderer, output_geometry, scale);
+        elements.extend(
+            self.menu
+                .as_ref()
+                .filter(|menu| menu.output == *output)
+                .map(|menu| menu.render_elements(renderer, scale))
+                .unwrap_or_default(),
+        );
+        elements.extend(
+            self.picker
+                .as_ref()
+                .filter(|picker| picker.output == *output)
+                .map(|picker| picker.render_elements(renderer, scale))
+                .unwrap_or_default(),
+        );
+        elements.extend(layer_elements(renderer, &layer_map, Layer::Overlay, scale));
+        if let Some(window) = self.fullscreen_window(output) {
+            let location = self.space.element_location(window).unwrap_or_default()
+                - window.geometry().loc
+                - output_geometry.loc;
+            let window = window.render_elements::<WindowElement<R>>(
+                renderer,
+                location.to_physical_precise_round(scale),
+                Scale::from(scale),
+                1.0,
+            );
+            elements.extend(window.into_iter().map(OutputElement::Window));
+        } else {
+            elements.extend(layer_elements(renderer, &layer_map, Layer::Top, scale));
+            elements.extend(
+                self.tab_elements(output)
+                    .into_iter()
+                    .map(OutputElement::Solid),
+            );
+            let windows =
+                self.space
+                    .render_elements_for_region(renderer, &output_geometry, scale, 1.0);
+            elements.extend(windows.into_iter().map(OutputElement::Window));
+        }
+        elements.extend(layer_elements(renderer, &layer_map, Layer::Bottom, scale));
+        elements.extend(layer_elements(
+            renderer,
+            &layer_map,
+            Layer::Background,
+            scale,
+        ));
+        elements
+    }
+
+    /// Tab at `location`, unless a floating or maximized window covers it
+    pub fn tab_under(&self, location: Point<f64, Logical>) -> Option<&Tab> {
+        if self
+            .element_under(location)
+            .is_some_and(|(window, _)| !window.state().is_empty())
+        {
+            return None;
+        }
+        self.tabs
+            .iter()
+            .find(|tab| tab.rect.to_f64().contains(location))
+    }
+
+    pub fn split_under(&self, location: Point<f64, Logical>) -> Option<&Split> {
+        self.splits
+            .iter()
+            .find(|split| split.rect.to_f64().contains(location))
+    }
+
+    pub fn layout_geometry(&self, layout: &Layout, id: Id) -> Option<Rectangle<i32, Logical>> {
+        let output = self.layout_output(layout)?;
+        self.layout_geometries.get(&(output.name(), id)).copied()
+    }
+
     pub fn render_window(&mut self, window: &Window, rect: Rectangle<i32, Logical>) -> Option<()> {
         window.resize(rect.size);
         self.space.map_element(window.clone(), rect.loc, false);
@@ -80,6 +398,43 @@ impl Renderer {
     }
 }
 
+render_elements! {
+    pub OutputElement<R> where R: ImportAll + ImportMem;
+    Window=WindowElement<R>,
+    Surface=WaylandSurfaceRenderElement<R>,
+    Solid=SolidColorRenderElement,
+    Thumbnail=RescaleRenderElement<WindowElement<R>>,
+    Memory=MemoryRenderBufferRenderElement<R>,
+}
+
+/// Render elements of the surfaces of `layer`, front to back
+fn layer_elements<R>(
+    renderer: &mut R,
+    layer_map: &LayerMap,
+    layer: Layer,
+    scale: f64,
+) -> Vec<OutputElement<R>>
+where
+    R: smithay::backend::renderer::Renderer + ImportAll + ImportMem,
+    R::TextureId: Clone + Texture + 'static,
+{
+    layer_map
+        .layers_on(layer)
+        .rev()
+        .filter_map(|surface| {
+            let location = layer_map.layer_geometry(surface)?.loc;
+            Some(surface.render_elements::<WaylandSurfaceRenderElement<R>>(
+                renderer,
+                location.to_physical_precise_round(scale),
+                Scale::from(scale),
+                1.0,
+            ))
+        })
+        .flatten()
+        .map(OutputElement::Surface)
+        .collect()
+}
+
 impl Deref for Renderer {
     type Target = Space<Window>;
 
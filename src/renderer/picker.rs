@@ -0,0 +1,223 @@
+use super::OutputElement;
+use crate::config::Config;
+use crate::window::render::WindowElement;
+use crate::window::Window;
+use crate::PocoWM;
+use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
+use smithay::backend::renderer::element::utils::RescaleRenderElement;
+use smithay::backend::renderer::element::{AsRenderElements as _, Kind};
+use smithay::backend::renderer::{ImportAll, ImportMem, Texture};
+use smithay::input::keyboard::Keysym;
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle, Scale, Size};
+
+/// Size thumbnails of minimized windows are fitted in
+const THUMBNAIL_SIZE: (i32, i32) = (240, 160);
+/// Space around each thumbnail
+const PADDING: i32 = 12;
+
+/// Thumbnails of the minimized windows, shown above everything on `output` while one of them is
+/// picked with the keyboard
+#[derive(Debug, Clone)]
+pub struct Picker {
+    pub output: Output,
+    /// Most recently minimized first
+    pub windows: Vec<Window>,
+    pub selected: usize,
+    output_size: Size<i32, Logical>,
+    background: SolidColorBuffer,
+    highlight: SolidColorBuffer,
+}
+
+impl PartialEq for Picker {
+    fn eq(&self, other: &Self) -> bool {
+        self.output == other.output
+            && self.windows == other.windows
+            && self.selected == other.selected
+    }
+}
+
+impl Picker {
+    pub fn new(
+        output: Output,
+        output_size: Size<i32, Logical>,
+        windows: Vec<Window>,
+        config: &Config,
+    ) -> Self {
+        let mut picker = Self {
+            output,
+            windows,
+            selected: 0,
+            output_size,
+            background: SolidColorBuffer::default(),
+            highlight: SolidColorBuffer::default(),
+        };
+        picker.background.set_color(config.tabs.inactive.0);
+        picker.highlight.set_color(config.tabs.active.0);
+        picker.resize_buffers();
+        picker
+    }
+
+    pub fn selected_window(&self) -> Option<&Window> {
+        self.windows.get(self.selected)
+    }
+
+    /// Moves the selection by `delta` thumbnails, wrapping around
+    pub fn select(&mut self, delta: isize) {
+        let len = self.windows.len() as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Removes a window that can't be picked anymore, returns whether any is left
+    pub fn remove(&mut self, window: &Window) -> bool {
+        let Some(index) = self.windows.iter().position(|w| w == window) else {
+            return true;
+        };
+        self.windows.remove(index);
+        if self.selected > index || self.selected == self.windows.len() {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.resize_buffers();
+        !self.windows.is_empty()
+    }
+
+    fn resize_buffers(&mut self) {
+        let (panel, _) = self.geometry();
+        self.background.resize(panel.size);
+        self.highlight
+            .resize((THUMBNAIL_SIZE.0 + PADDING, THUMBNAIL_SIZE.1 + PADDING));
+    }
+
+    /// The panel centered on the output and the cells of the thumbnails in it, relative to the
+    /// output
+    fn geometry(&self) -> (Rectangle<i32, Logical>, Vec<Rectangle<i32, Logical>>) {
+        let (w, h) = THUMBNAIL_SIZE;
+        let count = (self.windows.len() as i32).max(1);
+        let columns = ((self.output_size.w - PADDING) / (w + PADDING)).clamp(1, count);
+        let rows = (count - 1) / columns + 1;
+        let size = Size::from((
+            columns * (w + PADDING) + PADDING,
+            rows * (h + PADDING) + PADDING,
+        ));
+        let loc = Point::from((
+            (self.output_size.w - size.w) / 2,
+            (self.output_size.h - size.h) / 2,
+        ));
+        let cells = (0..self.windows.len() as i32)
+            .map(|i| {
+                let offset = Point::from((
+                    PADDING + i % columns * (w + PADDING),
+                    PADDING + i / columns * (h + PADDING),
+                ));
+                Rectangle::new(loc + offset, (w, h).into())
+            })
+            .collect();
+        (Rectangle::new(loc, size), cells)
+    }
+
+    /// Render elements of the picker, front to back
+    pub fn render_elements<R>(&self, renderer: &mut R, scale: f64) -> Vec<OutputElement<R>>
+    where
+        R: smithay::backend::renderer::Renderer + ImportAll + ImportMem,
+        R::TextureId: Send + Clone + Texture + 'static,
+    {
+        let (panel, cells) = self.geometry();
+        let mut elements = Vec::new();
+        self.windows.iter().zip(&cells).for_each(|(window, cell)| {
+            let geometry = window.geometry();
+            if geometry.size.w <= 0 || geometry.size.h <= 0 {
+                return;
+            }
+            // Scaled down to fit the cell, never up
+            let factor = f64::min(
+                cell.size.w as f64 / geometry.size.w as f64,
+                cell.size.h as f64 / geometry.size.h as f64,
+            )
+            .min(1.0);
+            let size = geometry.size.to_f64().upscale(factor).to_i32_round::<i32>();
+            let loc =
+                cell.loc + Point::from(((cell.size.w - size.w) / 2, (cell.size.h - size.h) / 2));
+            let origin = loc.to_physical_precise_round(scale);
+            let window = window.render_elements::<WindowElement<R>>(
+                renderer,
+                (loc - geometry.loc).to_physical_precise_round(scale),
+                Scale::from(scale),
+                1.0,
+            );
+            elements.extend(window.into_iter().map(|element| {
+                OutputElement::Thumbnail(RescaleRenderElement::from_element(
+                    element, origin, factor,
+                ))
+            }));
+        });
+        if let Some(cell) = cells.get(self.selected) {
+            let loc = cell.loc - Point::from((PADDING / 2, PADDING / 2));
+            elements.push(OutputElement::Solid(SolidColorRenderElement::from_buffer(
+                &self.highlight,
+                loc.to_physical_precise_round(scale),
+                scale,
+                1.0,
+                Kind::Unspecified,
+            )));
+        }
+        elements.push(OutputElement::Solid(SolidColorRenderElement::from_buffer(
+            &self.background,
+            panel.loc.to_physical_precise_round(scale),
+            scale,
+            1.0,
+            Kind::Unspecified,
+        )));
+        elements
+    }
+}
+
+impl PocoWM {
+    /// Shows the minimized windows on the focused output, to pick the one to restore
+    pub fn open_picker(&mut self) {
+        let windows = self.minimized.iter().rev().cloned().collect::<Vec<_>>();
+        if windows.is_empty() {
+            return;
+        }
+        let Some(output) = self.renderer.layout_output(&self.layout) else {
+            return;
+        };
+        let Some(geometry) = self.renderer.output_geometry(&output) else {
+            return;
+        };
+        self.renderer.picker = Some(Picker::new(output, geometry.size, windows, &self.config));
+    }
+
+    /// Handles a key pressed while the picker is shown: arrows and hjkl move the selection,
+    /// Return restores the selected window and Escape closes the picker
+    pub fn picker_key(&mut self, keysym: Keysym) {
+        let Some(picker) = &mut self.renderer.picker else {
+            return;
+        };
+        match keysym {
+            Keysym::Left | Keysym::Up | Keysym::h | Keysym::k | Keysym::ISO_Left_Tab => {
+                picker.select(-1)
+            }
+            Keysym::Right | Keysym::Down | Keysym::l | Keysym::j | Keysym::Tab => picker.select(1),
+            Keysym::Return | Keysym::KP_Enter | Keysym::space => {
+                let window = picker.selected_window().cloned();
+                self.renderer.picker = None;
+                window.map(|window| self.unminimize_window(&window));
+            }
+            Keysym::Escape => self.renderer.picker = None,
+            _ => {}
+        }
+    }
+
+    /// Forgets a minimized window, once restored or destroyed
+    pub(crate) fn forget_minimized(&mut self, window: &Window) {
+        self.minimized.retain(|w| w != window);
+        let Some(picker) = &mut self.renderer.picker else {
+            return;
+        };
+        if !picker.remove(window) {
+            self.renderer.picker = None;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::layout::{Id, LayoutType};
+use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
+use smithay::backend::renderer::element::Kind;
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle, Scale};
+use std::collections::HashMap;
+
+/// Space left between two tabs of the same container
+pub const TAB_SPACING: i32 = 2;
+
+/// A tab of a tabbed or stacked container, drawn above its visible child
+#[derive(Debug, Clone)]
+pub struct Tab {
+    /// Layout id of the child the tab shows
+    pub id: Id,
+    pub rect: Rectangle<i32, Logical>,
+    pub active: bool,
+    buffer: SolidColorBuffer,
+}
+
+impl PartialEq for Tab {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.rect == other.rect && self.active == other.active
+    }
+}
+
+impl Tab {
+    /// Updates `buffer` to the size and color of the tab, which draws it
+    pub fn new(
+        id: Id,
+        rect: Rectangle<i32, Logical>,
+        layout_type: LayoutType,
+        active: bool,
+        color: [f32; 4],
+        buffer: &mut SolidColorBuffer,
+    ) -> Self {
+        let mut size = rect.size;
+        match layout_type {
+            LayoutType::Stacked => size.h = (size.h - TAB_SPACING).max(1),
+            _ => size.w = (size.w - TAB_SPACING).max(1),
+        }
+        buffer.update(size, color);
+        Self {
+            id,
+            rect,
+            active,
+            buffer: buffer.clone(),
+        }
+    }
+
+    /// Render element of the tab, `origin` being the location of the output
+    pub fn render_element(
+        &self,
+        origin: Point<i32, Logical>,
+        scale: Scale<f64>,
+    ) -> SolidColorRenderElement {
+        SolidColorRenderElement::from_buffer(
+            &self.buffer,
+            (self.rect.loc - origin).to_physical_precise_round(scale),
+            scale,
+            1.0,
+            Kind::Unspecified,
+        )
+    }
+}
+
+/// Buffers of the tabs of each container, by output name as ids are per layout. They are kept
+/// across renders, so only the tabs whose size or color changed are damaged
+#[derive(Debug, Default)]
+pub struct TabBuffers(HashMap<(String, Id), Vec<SolidColorBuffer>>);
+
+impl PartialEq for TabBuffers {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().all(|(key, buffers)| {
+                other
+                    .0
+                    .get(key)
+                    .is_some_and(|other| other.len() == buffers.len())
+            })
+    }
+}
+
+impl TabBuffers {
+    /// Buffers of the `count` tabs of `container` on `output`
+    pub fn get(&mut self, output: &Output, container: Id, count: usize) -> &mut [SolidColorBuffer] {
+        let buffers = self.0.entry((output.name(), container)).or_default();
+        buffers.resize_with(count, Default::default);
+        buffers
+    }
+
+    /// Drops the buffers of the containers of `output` for which `f` returns false
+    pub fn retain(&mut self, output: &Output, mut f: impl FnMut(Id) -> bool) {
+        let name = output.name();
+        self.0.retain(|(output, id), _| *output != name || f(*id));
+    }
+}
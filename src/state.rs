@@ -1,48 +1,79 @@
+use crate::bindings::Bindings;
+use crate::config::Config;
+use crate::handlers::output_management::OutputManagementState;
+use crate::ipc::{IpcState, SOCKET_ENV};
 use crate::layout::Layout;
 use crate::renderer::Renderer;
+use crate::window::Window;
+use crate::workspace::Workspaces;
 use anyhow::{Context as _, Result};
+use getset::Getters;
 use smithay::desktop::PopupManager;
 use smithay::input::keyboard::Keysym;
 use smithay::input::{Seat, SeatState};
 use smithay::reexports::calloop::generic::Generic;
 use smithay::reexports::calloop::{self, EventLoop, LoopHandle, LoopSignal};
+use smithay::reexports::wayland_server::backend::protocol::ProtocolError;
 use smithay::reexports::wayland_server::backend::{ClientData, ClientId, DisconnectReason};
-use smithay::reexports::wayland_server::{Display, DisplayHandle};
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{Display, DisplayHandle, Resource as _};
 use smithay::wayland::compositor::{CompositorClientState, CompositorState};
+use smithay::wayland::cursor_shape::CursorShapeManagerState;
+use smithay::wayland::fractional_scale::FractionalScaleManagerState;
 use smithay::wayland::selection::data_device::DataDeviceState;
+use smithay::wayland::shell::wlr_layer::WlrLayerShellState;
 use smithay::wayland::shell::xdg::decoration::XdgDecorationState;
 use smithay::wayland::shell::xdg::XdgShellState;
 use smithay::wayland::shm::ShmState;
 use smithay::wayland::socket::ListeningSocketSource;
+use smithay::wayland::viewporter::ViewporterState;
+use smithay::wayland::xdg_activation::XdgActivationState;
 use smithay::wayland::xdg_foreign::XdgForeignState;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::os::unix::net::UnixStream;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-#[derive(Debug)]
+#[derive(Debug, Getters)]
 pub struct PocoWM {
     pub(crate) start_time: Instant,
     pub(crate) event_loop: Rc<RefCell<EventLoop<'static, Self>>>,
     pub(crate) loop_handle: LoopHandle<'static, Self>,
     pub(crate) display: DisplayHandle,
+    #[getset(get = "pub")]
     pub(crate) seat: Seat<Self>,
+    /// Layout of the active workspace
+    #[getset(get = "pub")]
     pub(crate) layout: Layout,
+    #[getset(get = "pub")]
+    pub(crate) workspaces: Workspaces,
+    /// Minimized windows of every workspace, the most recently minimized last
+    pub(crate) minimized: Vec<Window>,
     // pub(crate) floating_windows: Vec<Window>,
     // pub(crate) layout_manager: LayoutManager,
+    #[getset(get = "pub")]
     pub(crate) renderer: Renderer,
     pub(crate) loop_signal: LoopSignal,
+    #[getset(get = "pub")]
     pub(crate) socket_name: OsString,
     pub(crate) popups: PopupManager,
+    #[getset(get = "pub")]
+    pub(crate) config: Rc<Config>,
+    pub(crate) bindings: Bindings,
+    pub(crate) ipc: IpcState,
 
     pub(crate) seat_state: SeatState<Self>,
     pub(crate) data_device_state: DataDeviceState,
     pub(crate) compositor_state: CompositorState,
     pub(crate) xdg_shell_state: XdgShellState,
     // pub(crate) xdg_decoration_state: XdgDecorationState,
+    pub(crate) xdg_activation_state: XdgActivationState,
     pub(crate) xdg_foreign_state: XdgForeignState,
+    pub(crate) layer_shell_state: WlrLayerShellState,
+    pub(crate) output_management_state: OutputManagementState,
     pub(crate) shm_state: ShmState,
     pub(crate) pressed_keys: HashSet<Keysym>,
 }
@@ -62,25 +93,38 @@ impl PocoWM {
         let xdg_shell_state = XdgShellState::new::<Self>(&dh);
         // We have to init decorations state, even though we don't access to it
         let _xdg_decoration_state = XdgDecorationState::new::<Self>(&dh);
+        let xdg_activation_state = XdgActivationState::new::<Self>(&dh);
         let xdg_foreign_state = XdgForeignState::new::<Self>(&dh);
+        let layer_shell_state = WlrLayerShellState::new::<Self>(&dh);
+        let output_management_state = OutputManagementState::new::<Self>(&dh);
+        // Same for scaling, the states are only read by smithay
+        let _fractional_scale_state = FractionalScaleManagerState::new::<Self>(&dh);
+        let _viewporter_state = ViewporterState::new::<Self>(&dh);
+        // And for cursor shapes, which end up in `SeatHandler::cursor_image`
+        let _cursor_shape_state = CursorShapeManagerState::new::<Self>(&dh);
         let shm_state = ShmState::new::<Self>(&dh, vec![]);
         let popups = PopupManager::default();
+        let config = Config::load().unwrap_or_else(|err| {
+            eprintln!("{:?}\nUsing the default config", err);
+            Config::default()
+        });
+        let bindings = Bindings::from_config(&config);
+        let config = Rc::new(config);
 
         let mut seat: Seat<Self> = seat_state.new_wl_seat(&dh, "winit");
         let socket_name = socket.socket_name().to_owned();
         let layout = Layout::new();
         // let floating_windows = Vec::new();
         // let layout_manager = LayoutManager::new();
-        let renderer = Renderer::default();
+        let renderer = Renderer {
+            config: config.clone(),
+            ..Default::default()
+        };
 
         event_loop
             .handle()
             .insert_source(socket, move |client, _, state| {
-                let result = state
-                    .display
-                    .insert_client(client, Arc::new(ClientState::default()))
-                    .context("Failed to init client");
-                if let Err(err) = result {
+                if let Err(err) = state.insert_client(client) {
                     eprintln!("{:?}", err);
                 }
             })
@@ -100,17 +144,22 @@ impl PocoWM {
             )
             .context("Failed to init display event source")?;
 
-        seat.add_keyboard(Default::default(), 200, 25)
-            .context("Failed to init keyboard")?;
+        seat.add_keyboard(
+            Default::default(),
+            config.keyboard.repeat_delay,
+            config.keyboard.repeat_rate,
+        )
+        .context("Failed to init keyboard")?;
 
         seat.add_pointer();
 
-        Ok(Self {
+        let mut pocowm = Self {
             event_loop: Rc::new(RefCell::new(event_loop)),
             start_time,
             display: dh,
             seat,
             layout,
+            workspaces: Workspaces::default(),
             // floating_windows,
             // layout_manager,
             renderer,
@@ -118,6 +167,9 @@ impl PocoWM {
             loop_handle,
             socket_name,
             popups,
+            config,
+            bindings,
+            ipc: IpcState::default(),
 
             compositor_state,
             data_device_state,
@@ -125,22 +177,72 @@ impl PocoWM {
             shm_state,
             xdg_shell_state,
             // xdg_decoration_state,
+            xdg_activation_state,
             xdg_foreign_state,
+            layer_shell_state,
+            output_management_state,
             pressed_keys: HashSet::new(),
-        })
+            minimized: Vec::new(),
+        };
+        pocowm.init_config_watcher()?;
+        pocowm.init_ipc()?;
+
+        Ok(pocowm)
     }
 
     pub fn run(&mut self) -> Result<()> {
         println!("Listening on {}", self.socket_name.to_string_lossy());
+        std::env::set_var("WAYLAND_DISPLAY", &self.socket_name);
+        if let Some(path) = self.ipc_socket_path() {
+            std::env::set_var(SOCKET_ENV, path);
+        }
 
         self.event_loop
             .clone()
             .borrow_mut()
-            .run(None, self, |_| {})
+            .run(None, self, |state| state.flush_ipc())
             .context("Failed to run event loop")?;
 
         Ok(())
     }
+
+    /// Runs a single iteration of the event loop, then flushes clients
+    pub fn dispatch(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.event_loop
+            .clone()
+            .borrow_mut()
+            .dispatch(timeout, self)
+            .context("Failed to dispatch event loop")?;
+        self.flush_ipc();
+        self.display
+            .flush_clients()
+            .context("Failed to flush clients")?;
+        Ok(())
+    }
+
+    /// Adds a client connected through `stream`, bypassing the listening socket
+    pub fn insert_client(&mut self, stream: UnixStream) -> Result<()> {
+        self.display
+            .insert_client(stream, Arc::new(ClientState::default()))
+            .context("Failed to init client")?;
+        Ok(())
+    }
+
+    /// Disconnects the client of `surface`, for windows that don't close when asked to
+    pub fn kill_client(&mut self, surface: &WlSurface) {
+        let Some(client) = surface.client() else {
+            return;
+        };
+        client.kill(
+            &self.display,
+            ProtocolError {
+                code: 0,
+                object_id: 0,
+                object_interface: String::new(),
+                message: "Killed by the compositor".into(),
+            },
+        );
+    }
 }
 
 #[derive(Debug, Default)]
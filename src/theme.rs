@@ -0,0 +1,114 @@
+use crate::config::Color;
+use crate::window::{Window, WindowState};
+use crate::PocoWM;
+use serde::Deserialize;
+
+/// Colors of the borders and title bar of a window
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeColors {
+    /// Fully transparent borders aren't drawn at all
+    pub border: Color,
+    pub background: Color,
+    pub title: Color,
+}
+
+impl Default for ThemeColors {
+    fn default() -> Self {
+        Self {
+            border: Color([0.0, 0.0, 0.0, 0.0]),
+            background: Color([0.2, 0.2, 0.2, 1.0]),
+            title: Color([0.7, 0.7, 0.7, 1.0]),
+        }
+    }
+}
+
+/// Look of the borders and title bars, the `[theme]` of the config file or one of its `[themes]`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub focused: ThemeColors,
+    pub unfocused: ThemeColors,
+    /// Unfocused windows that asked for attention
+    pub urgent: ThemeColors,
+    /// Unfocused floating windows
+    pub floating: ThemeColors,
+    pub border_width: u32,
+    pub title_height: u32,
+    pub button_size: u32,
+    /// Space left of each button
+    pub button_gap: u32,
+    pub close_button: Color,
+    pub maximize_button: Color,
+    pub minimize_button: Color,
+    /// Family resolved with fontconfig, or path to a font file
+    pub font: String,
+    /// Height of the font in logical pixels
+    pub font_size: f32,
+    /// Show the app_id after the title
+    pub app_id: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            focused: ThemeColors {
+                border: Color([0.0, 0.0, 1.0, 1.0]),
+                background: Color([0.0, 0.0, 0.0, 1.0]),
+                title: Color([1.0, 1.0, 1.0, 1.0]),
+            },
+            unfocused: ThemeColors::default(),
+            urgent: ThemeColors {
+                border: Color([0.8, 0.2, 0.2, 1.0]),
+                background: Color([0.5, 0.1, 0.1, 1.0]),
+                title: Color([1.0, 1.0, 1.0, 1.0]),
+            },
+            floating: ThemeColors {
+                background: Color([0.1, 0.1, 0.3, 1.0]),
+                ..Default::default()
+            },
+            border_width: 10,
+            title_height: 24,
+            button_size: 16,
+            button_gap: 4,
+            close_button: Color([1.0, 0.0, 0.0, 1.0]),
+            maximize_button: Color([0.0, 1.0, 0.0, 1.0]),
+            minimize_button: Color([0.0, 0.0, 1.0, 1.0]),
+            font: "sans-serif".into(),
+            font_size: 13.0,
+            app_id: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Colors of `window`, by focus, then urgency, then floating state
+    pub fn colors(&self, window: &Window) -> &ThemeColors {
+        if window.is_focused() {
+            &self.focused
+        } else if *window.urgent() {
+            &self.urgent
+        } else if window.state().contains(WindowState::FLOATING) {
+            &self.floating
+        } else {
+            &self.unfocused
+        }
+    }
+}
+
+impl PocoWM {
+    /// Switches to the theme `name` of the config file, `default` being its `[theme]`
+    pub fn set_theme(&mut self, name: &str) {
+        let name = (name != "default").then(|| name.to_owned());
+        if name
+            .as_ref()
+            .is_some_and(|name| !self.config.themes.contains_key(name))
+        {
+            eprintln!("Unknown theme: {}", name.unwrap_or_default());
+            return;
+        }
+        let mut config = (*self.config).clone();
+        config.active_theme = name;
+        self.apply_config(config);
+    }
+}
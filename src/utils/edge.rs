@@ -1,4 +1,5 @@
 use bitflags::bitflags;
+use smithay::input::pointer::CursorIcon;
 use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 
 bitflags! {
@@ -25,6 +26,25 @@ impl From<xdg_toplevel::ResizeEdge> for Edge {
         }
     }
 }
+/// Cursor shown while resizing from the edges
+impl From<Edge> for CursorIcon {
+    fn from(edges: Edge) -> Self {
+        let icons = [
+            (Edge::TOP | Edge::LEFT, CursorIcon::NwResize),
+            (Edge::TOP | Edge::RIGHT, CursorIcon::NeResize),
+            (Edge::BOTTOM | Edge::LEFT, CursorIcon::SwResize),
+            (Edge::BOTTOM | Edge::RIGHT, CursorIcon::SeResize),
+            (Edge::TOP, CursorIcon::NResize),
+            (Edge::BOTTOM, CursorIcon::SResize),
+            (Edge::LEFT, CursorIcon::WResize),
+            (Edge::RIGHT, CursorIcon::EResize),
+        ];
+        icons
+            .into_iter()
+            .find(|(edge, _)| edges.contains(*edge))
+            .map_or(CursorIcon::Move, |(_, icon)| icon)
+    }
+}
 impl Edge {
     pub fn get_vertical(&self) -> Edge {
         self.intersection(Self::TOP | Self::BOTTOM)
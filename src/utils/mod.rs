@@ -1,3 +1,15 @@
 mod edge;
+pub mod text;
 
 pub use edge::Edge;
+
+use smithay::utils::{Logical, Physical, Point, Rectangle, Scale, Size};
+
+/// Converts `rect` to physical pixels by rounding its edges, so that adjacent rectangles neither
+/// overlap nor leave gaps at fractional scales
+pub fn physical_rect(rect: Rectangle<i32, Logical>, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+    let round = |point: Point<i32, Logical>| point.to_f64().to_physical(scale).to_i32_round();
+    let loc = round(rect.loc);
+    let end = round(rect.loc + Point::from((rect.size.w, rect.size.h)));
+    Rectangle::new(loc, Size::from((end.x - loc.x, end.y - loc.y)))
+}
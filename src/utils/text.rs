@@ -0,0 +1,125 @@
+use ab_glyph::{point, Font as _, FontArc, GlyphId, PxScale, ScaleFont as _};
+use anyhow::{anyhow, Context as _, Result};
+use smithay::utils::{Buffer, Size};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Put at the end of text too long to fit
+const ELLIPSIS: char = '…';
+
+thread_local! {
+    /// Fonts by the name they were asked with, `None` when they couldn't be loaded
+    static FONTS: RefCell<HashMap<String, Option<FontArc>>> = RefCell::default();
+}
+
+/// Loads a font the first time it is asked for, `name` being a path to a font file or a family
+/// resolved with fontconfig
+pub fn font(name: &str) -> Option<FontArc> {
+    FONTS.with_borrow_mut(|fonts| {
+        fonts
+            .entry(name.to_owned())
+            .or_insert_with(|| load_font(name).map_err(|err| eprintln!("{:?}", err)).ok())
+            .clone()
+    })
+}
+
+fn load_font(name: &str) -> Result<FontArc> {
+    let path = if Path::new(name).is_file() {
+        name.to_owned()
+    } else {
+        let output = Command::new("fc-match")
+            .arg("--format=%{file}")
+            .arg(name)
+            .output()
+            .context("Failed to run fc-match")?;
+        String::from_utf8(output.stdout).context("Invalid font path")?
+    };
+    let data = std::fs::read(&path).with_context(|| format!("Failed to read font {}", path))?;
+    FontArc::try_from_vec(data).map_err(|_| anyhow!("Invalid font: {}", path))
+}
+
+/// Glyphs of `text` along with their horizontal position, `px` being the height of the font
+fn layout(font: &FontArc, text: &str, px: f32) -> (Vec<(GlyphId, f32)>, f32) {
+    let font = font.as_scaled(PxScale::from(px));
+    let mut caret = 0.0;
+    let mut previous = None;
+    let glyphs = text
+        .chars()
+        .map(|c| {
+            let id = font.glyph_id(c);
+            caret += previous.map_or(0.0, |previous| font.kern(previous, id));
+            previous = Some(id);
+            let glyph = (id, caret);
+            caret += font.h_advance(id);
+            glyph
+        })
+        .collect();
+    (glyphs, caret)
+}
+
+/// Shortens `text` and ends it with an ellipsis until it is at most `width` pixels wide
+pub fn ellipsize(font: &FontArc, text: &str, px: f32, width: f32) -> String {
+    let (glyphs, text_width) = layout(font, text, px);
+    if text_width <= width {
+        return text.to_owned();
+    }
+    let scaled = font.as_scaled(PxScale::from(px));
+    let ellipsis = scaled.h_advance(scaled.glyph_id(ELLIPSIS));
+    if ellipsis > width {
+        return String::new();
+    }
+    // Trailing spaces don't count, they are cut along with the rest
+    let chars = text.chars().collect::<Vec<_>>();
+    let kept = glyphs
+        .iter()
+        .zip(&chars)
+        .enumerate()
+        .filter(|(_, (_, c))| !c.is_whitespace())
+        .take_while(|(_, ((id, x), _))| x + scaled.h_advance(*id) + ellipsis <= width)
+        .last()
+        .map_or(0, |(i, _)| i + 1);
+    chars[..kept].iter().chain([&ELLIPSIS]).collect()
+}
+
+/// Draws `text` in `color` on a transparent ARGB8888 image of `size`, vertically centered and
+/// ellipsized to fit its width
+pub fn render_text(
+    font: &FontArc,
+    text: &str,
+    px: f32,
+    color: [f32; 4],
+    size: Size<i32, Buffer>,
+) -> Vec<u8> {
+    let (width, height) = (size.w.max(0) as usize, size.h.max(0) as usize);
+    let mut pixels = vec![0; width * height * 4];
+    let text = ellipsize(font, text, px, width as f32);
+    let scaled = font.as_scaled(PxScale::from(px));
+    let baseline = (height as f32 + scaled.ascent() + scaled.descent()) / 2.0;
+    let (glyphs, _) = layout(font, &text, px);
+    glyphs.into_iter().for_each(|(id, x)| {
+        let glyph = id.with_scale_and_position(PxScale::from(px), point(x, baseline));
+        let Some(outline) = font.outline_glyph(glyph) else {
+            return;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|x, y, coverage| {
+            let x = bounds.min.x as i32 + x as i32;
+            let y = bounds.min.y as i32 + y as i32;
+            if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                return;
+            }
+            let i = (y as usize * width + x as usize) * 4;
+            let alpha = coverage.min(1.0) * color[3];
+            // Overlapping glyphs keep the most covered value
+            if alpha * 255.0 <= pixels[i + 3] as f32 {
+                return;
+            }
+            // Premultiplied, in the little endian byte order of ARGB8888
+            let pixel = [color[2] * alpha, color[1] * alpha, color[0] * alpha, alpha];
+            pixels[i..i + 4].copy_from_slice(&pixel.map(|c| (c * 255.0).round() as u8));
+        });
+    });
+    pixels
+}
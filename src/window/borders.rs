@@ -1,16 +1,13 @@
-use crate::utils::Edge;
+use crate::utils::{physical_rect, Edge};
 
 use super::Window;
 use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
 use smithay::backend::renderer::element::{AsRenderElements, Kind};
 use smithay::backend::renderer::Renderer;
 use smithay::render_elements;
-use smithay::utils::{Logical, Physical, Point, Scale};
+use smithay::utils::{Logical, Physical, Point, Rectangle, Scale};
 use std::cell::RefCell;
 
-pub const BORDER_SIZE: u32 = 10;
-pub const BORDER_COLOR: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
-
 #[derive(Debug, Clone, Default)]
 struct BordersBuffers {
     top: SolidColorBuffer,
@@ -20,15 +17,13 @@ struct BordersBuffers {
 }
 
 impl BordersBuffers {
-    pub fn update(&mut self, borders: &Borders) {
-        let width = borders.window.geometry().size.w + 2 * BORDER_SIZE as i32;
-        let height = borders.window.geometry().size.h + 2 * BORDER_SIZE as i32;
-        self.top.update((width, BORDER_SIZE as i32), BORDER_COLOR);
-        self.bottom
-            .update((width, BORDER_SIZE as i32), BORDER_COLOR);
-        self.left.update((BORDER_SIZE as i32, height), BORDER_COLOR);
-        self.right
-            .update((BORDER_SIZE as i32, height), BORDER_COLOR);
+    /// Sizes the buffers in physical pixels, from the rects given by `Borders::rects`
+    pub fn update(&mut self, rects: &[Rectangle<i32, Physical>; 4], color: [f32; 4]) {
+        let size = |rect: &Rectangle<i32, Physical>| (rect.size.w, rect.size.h);
+        self.top.update(size(&rects[0]), color);
+        self.bottom.update(size(&rects[1]), color);
+        self.left.update(size(&rects[2]), color);
+        self.right.update(size(&rects[3]), color);
     }
 }
 
@@ -68,6 +63,19 @@ impl Borders {
         }
         edges
     }
+    /// Top, bottom, left and right borders, relative to the window geometry
+    fn rects(&self, scale: Scale<f64>) -> [Rectangle<i32, Physical>; 4] {
+        let size = self.window.config().theme().border_width as i32;
+        let window_size = self.window.geometry().size;
+        let (width, height) = (window_size.w + 2 * size, window_size.h + 2 * size);
+        [
+            Rectangle::new((-size, -size).into(), (width, size).into()),
+            Rectangle::new((-size, window_size.h).into(), (width, size).into()),
+            Rectangle::new((-size, -size).into(), (size, height).into()),
+            Rectangle::new((window_size.w, -size).into(), (size, height).into()),
+        ]
+        .map(|rect| physical_rect(rect, scale))
+    }
 }
 
 render_elements! {
@@ -89,8 +97,9 @@ where
         scale: Scale<f64>,
         alpha: f32,
     ) -> Vec<C> {
+        let rects = self.rects(scale);
         let mut buffers = self.buffers.borrow_mut();
-        buffers.update(self);
+        buffers.update(&rects, self.window.colors().border.0);
         location += self
             .window
             .geometry()
@@ -98,41 +107,21 @@ where
             .to_f64()
             .to_physical(scale)
             .to_i32_round();
-        vec![
-            SolidColorRenderElement::from_buffer(
-                &buffers.top,
-                location - Point::from((BORDER_SIZE as i32, BORDER_SIZE as i32)),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
-            SolidColorRenderElement::from_buffer(
-                &buffers.bottom,
-                location
-                    + Point::from((0 - BORDER_SIZE as i32, self.window.geometry().size.h as i32)),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
-            SolidColorRenderElement::from_buffer(
-                &buffers.left,
-                location - Point::from((BORDER_SIZE as i32, BORDER_SIZE as i32)),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
-            SolidColorRenderElement::from_buffer(
-                &buffers.right,
-                location
-                    + Point::from((self.window.geometry().size.w as i32, 0 - BORDER_SIZE as i32)),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
-        ]
-        .into_iter()
-        .map(BordersElement::from)
-        .map(C::from)
-        .collect::<Vec<_>>()
+        // The buffers are already in physical pixels, scaling them again would blur the edges
+        [&buffers.top, &buffers.bottom, &buffers.left, &buffers.right]
+            .into_iter()
+            .zip(rects)
+            .map(|(buffer, rect)| {
+                SolidColorRenderElement::from_buffer(
+                    buffer,
+                    location + rect.loc,
+                    1.0,
+                    alpha,
+                    Kind::Unspecified,
+                )
+            })
+            .map(BordersElement::from)
+            .map(C::from)
+            .collect::<Vec<_>>()
     }
 }
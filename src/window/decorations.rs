@@ -1,27 +1,49 @@
 use std::cell::RefCell;
 
+use crate::theme::{Theme, ThemeColors};
+use crate::utils::{physical_rect, text};
 use crate::window::Window;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::{
+    MemoryRenderBuffer, MemoryRenderBufferRenderElement,
+};
 use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
 use smithay::backend::renderer::element::{AsRenderElements, Kind};
-use smithay::backend::renderer::Renderer;
+use smithay::backend::renderer::{ImportMem, Renderer};
 use smithay::render_elements;
-use smithay::utils::{Logical, Physical, Point, Scale};
+use smithay::utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Size, Transform};
 
-pub const DECORATIONS_HEIGHT: u32 = BUTTON_SIZE + 2 * BUTTON_GAP;
-pub const BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-pub const CLOSE_BUTTON_COLOR: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
-pub const MAXIMIZE_BUTTON_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
-pub const MINIMIZE_BUTTON_COLOR: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
-pub const BUTTON_SIZE: u32 = 16;
-pub const BUTTON_GAP: u32 = 4;
-
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Button {
     Close,
     Maximize,
     Minimize,
 }
 
+/// How a button is drawn, from the pointer over it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Highlight {
+    #[default]
+    None,
+    Hovered,
+    /// Pressed with the pointer still over it
+    Pressed,
+}
+
+impl Highlight {
+    /// `color` lightened when hovered and darkened when pressed, premultiplied alpha kept
+    fn apply(self, color: [f32; 4]) -> [f32; 4] {
+        let target = match self {
+            Highlight::None => return color,
+            Highlight::Hovered => color[3],
+            Highlight::Pressed => 0.0,
+        };
+        let [r, g, b, a] = color;
+        let [r, g, b] = [r, g, b].map(|c| c + (target - c) * 0.3);
+        [r, g, b, a]
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 struct DecorationsBuffers {
     background: SolidColorBuffer,
@@ -31,31 +53,46 @@ struct DecorationsBuffers {
 }
 
 impl DecorationsBuffers {
-    pub fn update(&mut self, decorations: &Decorations) {
-        self.background.update(
-            (
-                decorations.window.geometry().size.w,
-                DECORATIONS_HEIGHT as i32,
-            ),
-            BACKGROUND_COLOR,
-        );
+    /// Sizes the buffers in physical pixels, from the rects given by `Decorations::rects`
+    pub fn update(
+        &mut self,
+        rects: &[Rectangle<i32, Physical>; 4],
+        theme: &Theme,
+        colors: &ThemeColors,
+        highlights: [Highlight; 3],
+    ) {
+        let size = |rect: &Rectangle<i32, Physical>| (rect.size.w, rect.size.h);
         self.close_button
-            .update((BUTTON_SIZE as i32, BUTTON_SIZE as i32), CLOSE_BUTTON_COLOR);
+            .update(size(&rects[0]), highlights[0].apply(theme.close_button.0));
         self.maximize_button.update(
-            (BUTTON_SIZE as i32, BUTTON_SIZE as i32),
-            MAXIMIZE_BUTTON_COLOR,
+            size(&rects[1]),
+            highlights[1].apply(theme.maximize_button.0),
         );
         self.minimize_button.update(
-            (BUTTON_SIZE as i32, BUTTON_SIZE as i32),
-            MINIMIZE_BUTTON_COLOR,
+            size(&rects[2]),
+            highlights[2].apply(theme.minimize_button.0),
         );
+        self.background.update(size(&rects[3]), colors.background.0);
     }
 }
 
+/// Everything the rasterized title depends on
+#[derive(Debug, Clone, PartialEq)]
+struct TitleKey {
+    text: String,
+    font: String,
+    font_size: f32,
+    color: [f32; 4],
+    size: Size<i32, Buffer>,
+    scale: i32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Decorations {
     window: Window,
     buffers: RefCell<DecorationsBuffers>,
+    /// Rasterized title, drawn again only when its key changes
+    title: RefCell<Option<(TitleKey, MemoryRenderBuffer)>>,
 }
 
 impl PartialEq for Decorations {
@@ -70,94 +107,175 @@ impl Decorations {
         Self {
             window,
             buffers: RefCell::new(DecorationsBuffers::default()),
+            title: RefCell::new(None),
         }
     }
     pub fn get_button(&self, loc: Point<f64, Logical>) -> Option<Button> {
-        if loc.x < BUTTON_GAP as f64 || loc.y < BUTTON_GAP as f64 {
-            return None;
+        let config = self.window.config().clone();
+        Self::buttons(config.theme())
+            .into_iter()
+            .find(|(_, rect)| rect.to_f64().contains(loc))
+            .map(|(button, _)| button)
+    }
+    /// Close, maximize and minimize buttons from the left of the bar, vertically centered
+    fn buttons(theme: &Theme) -> [(Button, Rectangle<i32, Logical>); 3] {
+        let (size, gap) = (theme.button_size as i32, theme.button_gap as i32);
+        let y = (theme.title_height as i32 - size) / 2;
+        let rect =
+            |i: i32| Rectangle::new((i * size + (i + 1) * gap, y).into(), (size, size).into());
+        [
+            (Button::Close, rect(0)),
+            (Button::Maximize, rect(1)),
+            (Button::Minimize, rect(2)),
+        ]
+    }
+    /// Highlight of `button`, whose rect is `rect`, from the pointer location and the button it
+    /// pressed
+    fn highlight(&self, button: Button, rect: Rectangle<i32, Logical>) -> Highlight {
+        let hovered = self
+            .window
+            .pointer_location()
+            .is_some_and(|loc| rect.to_f64().contains(loc));
+        match self.window.seat_data().pressed_button {
+            Some(pressed) if pressed == button && hovered => Highlight::Pressed,
+            Some(_) => Highlight::None,
+            None if hovered => Highlight::Hovered,
+            None => Highlight::None,
         }
-        if loc.x < BUTTON_SIZE as f64 + BUTTON_GAP as f64
-            && loc.y < BUTTON_SIZE as f64 + BUTTON_GAP as f64
-        {
-            return Some(Button::Close);
+    }
+    /// Close, maximize and minimize buttons then background, relative to the top left corner
+    fn rects(&self, theme: &Theme, scale: Scale<f64>) -> [Rectangle<i32, Physical>; 4] {
+        let [close, maximize, minimize] = Self::buttons(theme).map(|(_, rect)| rect);
+        let width = self.window.geometry().size.w;
+        let background = Rectangle::from_size((width, theme.title_height as i32).into());
+        [close, maximize, minimize, background].map(|rect| physical_rect(rect, scale))
+    }
+    /// Area of the title, between the buttons and the right end of the bar
+    fn title_rect(&self, theme: &Theme) -> Rectangle<i32, Logical> {
+        let gap = theme.button_gap as i32;
+        let x = 3 * (theme.button_size as i32 + gap) + gap;
+        let width = self.window.geometry().size.w - x - gap;
+        Rectangle::new((x, 0).into(), (width, theme.title_height as i32).into())
+    }
+    /// The title of the window, followed by its app_id if enabled
+    fn title_text(&self, theme: &Theme) -> String {
+        let title = self.window.title().unwrap_or_default();
+        match self.window.app_id().filter(|_| theme.app_id) {
+            Some(app_id) if !title.is_empty() => format!("{} — {}", title, app_id),
+            Some(app_id) => app_id,
+            None => title,
         }
-        if loc.x < 2.0 * BUTTON_SIZE as f64 + 2.0 * BUTTON_GAP as f64
-            && loc.y < BUTTON_SIZE as f64 + BUTTON_GAP as f64
-        {
-            return Some(Button::Maximize);
+    }
+    /// Buffer of the title, rasterized at the scale of the output rounded up so it stays sharp
+    fn title_buffer(
+        &self,
+        theme: &Theme,
+        colors: &ThemeColors,
+        scale: Scale<f64>,
+    ) -> Option<MemoryRenderBuffer> {
+        let buffer_scale = scale.x.ceil().max(1.0) as i32;
+        let rect = self.title_rect(theme);
+        let key = TitleKey {
+            text: self.title_text(theme),
+            font: theme.font.clone(),
+            font_size: theme.font_size,
+            color: colors.title.0,
+            size: rect.size.to_buffer(buffer_scale, Transform::Normal),
+            scale: buffer_scale,
+        };
+        if key.text.is_empty() || key.size.w <= 0 {
+            return None;
         }
-        if loc.x < 3.0 * BUTTON_SIZE as f64 + 3.0 * BUTTON_GAP as f64
-            && loc.y < BUTTON_SIZE as f64 + BUTTON_GAP as f64
-        {
-            return Some(Button::Minimize);
+        let mut title = self.title.borrow_mut();
+        if let Some((_, buffer)) = title.as_ref().filter(|(old, _)| *old == key) {
+            return Some(buffer.clone());
         }
-
-        None
+        let font = text::font(&key.font)?;
+        let pixels = text::render_text(
+            &font,
+            &key.text,
+            key.font_size * buffer_scale as f32,
+            key.color,
+            key.size,
+        );
+        let buffer = MemoryRenderBuffer::from_slice(
+            &pixels,
+            Fourcc::Argb8888,
+            key.size,
+            buffer_scale,
+            Transform::Normal,
+            None,
+        );
+        *title = Some((key, buffer.clone()));
+        Some(buffer)
     }
 }
 
 render_elements! {
-    pub DecorationsElement;
+    pub DecorationsElement<R> where R: ImportMem;
     Decorations=SolidColorRenderElement,
+    Title=MemoryRenderBufferRenderElement<R>,
 }
 
 impl<R> AsRenderElements<R> for Decorations
 where
-    R: Renderer,
-    <R as Renderer>::TextureId: 'static,
+    R: Renderer + ImportMem,
+    <R as Renderer>::TextureId: Send + Clone + 'static,
 {
-    type RenderElement = DecorationsElement;
+    type RenderElement = DecorationsElement<R>;
 
     fn render_elements<C: From<Self::RenderElement>>(
         &self,
-        _renderer: &mut R,
+        renderer: &mut R,
         location: Point<i32, Physical>,
         scale: Scale<f64>,
         alpha: f32,
     ) -> Vec<C> {
-        let mut buffers = self.buffers.borrow_mut();
-        buffers.update(self);
-        vec![
-            SolidColorRenderElement::from_buffer(
-                &buffers.close_button,
-                location + Point::from((BUTTON_GAP as i32, BUTTON_GAP as i32)),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
-            SolidColorRenderElement::from_buffer(
-                &buffers.maximize_button,
-                location
-                    + Point::from((
-                        BUTTON_SIZE as i32 + 2 * BUTTON_GAP as i32,
-                        BUTTON_GAP as i32,
-                    )),
-                scale,
-                alpha,
+        let config = self.window.config().clone();
+        let theme = config.theme();
+        let colors = self.window.colors();
+        let title = self.title_buffer(theme, &colors, scale).and_then(|buffer| {
+            let loc = location + physical_rect(self.title_rect(theme), scale).loc;
+            MemoryRenderBufferRenderElement::from_buffer(
+                renderer,
+                loc.to_f64(),
+                &buffer,
+                Some(alpha),
+                None,
+                None,
                 Kind::Unspecified,
-            ),
-            SolidColorRenderElement::from_buffer(
-                &buffers.minimize_button,
-                location
-                    + Point::from((
-                        2 * BUTTON_SIZE as i32 + 3 * BUTTON_GAP as i32,
-                        BUTTON_GAP as i32,
-                    )),
-                scale,
-                alpha,
-                Kind::Unspecified,
-            ),
+            )
+            .map_err(|err| eprintln!("{:?}", err))
+            .ok()
+        });
+        let rects = self.rects(theme, scale);
+        let mut buffers = self.buffers.borrow_mut();
+        let highlights = Self::buttons(theme).map(|(button, rect)| self.highlight(button, rect));
+        buffers.update(&rects, theme, &colors, highlights);
+        // The buffers are already in physical pixels, scaling them again would blur the edges
+        let solid = [
+            &buffers.close_button,
+            &buffers.maximize_button,
+            &buffers.minimize_button,
+            &buffers.background,
+        ]
+        .into_iter()
+        .zip(rects)
+        .map(|(buffer, rect)| {
             SolidColorRenderElement::from_buffer(
-                &buffers.background,
-                location,
-                scale,
+                buffer,
+                location + rect.loc,
+                1.0,
                 alpha,
                 Kind::Unspecified,
-            ),
-        ]
-        .into_iter()
-        .map(DecorationsElement::from)
-        .map(C::from)
-        .collect::<Vec<_>>()
+            )
+        })
+        .map(DecorationsElement::from);
+        title
+            .map(DecorationsElement::from)
+            .into_iter()
+            .chain(solid)
+            .map(C::from)
+            .collect::<Vec<_>>()
     }
 }
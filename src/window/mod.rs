@@ -5,15 +5,22 @@ pub mod seat;
 
 use bitflags::bitflags;
 use borders::Borders;
-use decorations::{Decorations, DECORATIONS_HEIGHT};
+use decorations::{Button, Decorations};
 use derive_more::{Deref, DerefMut};
 use getset::{Getters, Setters};
 use smithay::desktop::Window as InnerWindow;
 use smithay::reexports::wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode;
+use smithay::output::Output;
 use smithay::utils::{Logical, Point, Rectangle, Size};
-use smithay::wayland::shell::xdg::ToplevelSurface;
+use smithay::wayland::compositor::{send_surface_state, with_states};
+use smithay::wayland::fractional_scale::with_fractional_scale;
+use smithay::wayland::shell::xdg::{
+    ToplevelSurface, XdgToplevelSurfaceData, XdgToplevelSurfaceRoleAttributes,
+};
 use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
 
+use crate::config::Config;
 use crate::utils::Edge;
 
 /* #[derive(Debug, Clone, Default, PartialEq, Eq, IsVariant)]
@@ -63,6 +70,12 @@ bitflags! {
 pub struct WindowSeatData {
     pub pointer_location: Option<Point<f64, Logical>>,
     pub touch_location: Option<Point<f64, Logical>>,
+    /// Time of the last left click on the title bar, to detect double clicks
+    pub last_title_click: Option<u32>,
+    /// Title bar button pressed, clicked if the pointer is released over it
+    pub pressed_button: Option<Button>,
+    /// Whether the client surface has the pointer, which it doesn't over the title bar
+    pub pointer_in_surface: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Getters, Setters)]
@@ -70,21 +83,29 @@ pub struct WindowSeatData {
 struct WindowUserData {
     state: WindowState,
     is_focused: bool,
+    /// Asked for attention while unfocused, until it gets the focus
+    urgent: bool,
     floating_rect: Rectangle<i32, Logical>,
     seat_data: WindowSeatData,
     decorations: Option<Decorations>,
     borders: Borders,
+    config: Rc<Config>,
+    /// Outputs the window overlaps
+    outputs: Vec<Output>,
 }
 
 impl WindowUserData {
-    pub fn new(window: Window) -> Self {
+    pub fn new(window: Window, config: Rc<Config>) -> Self {
         Self {
             state: Default::default(),
             is_focused: Default::default(),
+            urgent: Default::default(),
             floating_rect: Default::default(),
             seat_data: Default::default(),
             decorations: Default::default(),
             borders: Borders::new(window),
+            config,
+            outputs: Vec::new(),
         }
     }
 }
@@ -109,9 +130,13 @@ macro_rules! generate_getter {
 }
 
 impl Window {
-    #[inline]
-    pub fn from_surface(surface: ToplevelSurface) -> Self {
-        Self::from(InnerWindow::new_wayland_window(surface))
+    pub fn from_surface(surface: ToplevelSurface, config: Rc<Config>) -> Self {
+        let window = Self::from(InnerWindow::new_wayland_window(surface));
+        window
+            .inner()
+            .user_data()
+            .insert_if_missing(|| RefCell::new(WindowUserData::new(window.clone(), config)));
+        window
     }
     #[inline]
     pub fn inner(&self) -> &InnerWindow {
@@ -120,7 +145,25 @@ impl Window {
     fn user_data(&self) -> &RefCell<WindowUserData> {
         self.inner()
             .user_data()
-            .get_or_insert(|| RefCell::new(WindowUserData::new(self.clone())))
+            .get()
+            .expect("Window created without user data")
+    }
+
+    fn with_toplevel_data<T>(
+        &self,
+        f: impl FnOnce(&XdgToplevelSurfaceRoleAttributes) -> Option<T>,
+    ) -> Option<T> {
+        let xdg = self.toplevel()?;
+        with_states(xdg.wl_surface(), |states| {
+            let data = states.data_map.get::<XdgToplevelSurfaceData>()?;
+            f(&data.lock().unwrap())
+        })
+    }
+    pub fn app_id(&self) -> Option<String> {
+        self.with_toplevel_data(|data| data.app_id.clone())
+    }
+    pub fn title(&self) -> Option<String> {
+        self.with_toplevel_data(|data| data.title.clone())
     }
 
     pub fn add_decorations(&self) {
@@ -147,17 +190,31 @@ impl Window {
         *self.decorations_mut() = None;
     }
 
-    pub fn resize(&self, mut size: Size<i32, Logical>) {
-        if self.decorations().is_some() {
-            size.h -= DECORATIONS_HEIGHT as i32;
+    /// Whether the window uses server side decorations, even while they are hidden in fullscreen
+    pub fn is_decorated(&self) -> bool {
+        self.decorations().is_some()
+    }
+
+    pub fn toggle_decorations(&self) {
+        if self.is_decorated() {
+            self.remove_decorations();
+        } else {
+            self.add_decorations();
         }
+    }
+
+    pub fn resize(&self, mut size: Size<i32, Logical>) {
+        size.h -= self.title_height();
         let Some(xdg) = self.0.toplevel() else {
             return;
         };
         xdg.with_pending_state(|state| {
             state.size = Some(size);
         });
-        xdg.send_configure();
+        // The initial configure waits for the initial commit, and takes the pending size
+        if xdg.is_initial_configure_sent() {
+            xdg.send_configure();
+        }
     }
 
     pub fn is_focused(&self) -> bool {
@@ -166,12 +223,37 @@ impl Window {
     pub fn focus(&self) {
         self.set_activated(true);
         *self.get_is_focused_mut() = true;
+        *self.urgent_mut() = false;
     }
     pub fn unfocus(&self) {
         self.set_activated(false);
         *self.get_is_focused_mut() = false;
     }
 
+    /// Tells the surfaces the scale and transform to draw at, those of the output with the
+    /// highest scale when the window overlaps several
+    pub fn send_scale(&self) {
+        let output = self
+            .outputs()
+            .iter()
+            .max_by(|a, b| {
+                let a = a.current_scale().fractional_scale();
+                a.total_cmp(&b.current_scale().fractional_scale())
+            })
+            .cloned();
+        let Some(output) = output else {
+            return;
+        };
+        let scale = output.current_scale();
+        let transform = output.current_transform();
+        self.inner().with_surfaces(|surface, states| {
+            send_surface_state(surface, states, scale.integer_scale(), transform);
+            with_fractional_scale(states, |fractional| {
+                fractional.set_preferred_scale(scale.fractional_scale())
+            });
+        });
+    }
+
     pub fn get_edge_under(&self, location: Point<f64, Logical>) -> Edge {
         let mut edges = Edge::empty();
         if location.x < self.geometry().size.w as f64 / 2.0 {
@@ -193,8 +275,11 @@ impl Window {
     generate_getter!(pub floating_rect: Rectangle<i32, Logical>);
     generate_getter!(pub seat_data: WindowSeatData);
     generate_getter!(is_focused as get_is_focused: bool);
+    generate_getter!(pub urgent: bool);
     generate_getter!(decorations: Option<Decorations>);
     generate_getter!(borders: Borders);
+    generate_getter!(pub(crate) config: Rc<Config>);
+    generate_getter!(pub(crate) outputs: Vec<Output>);
 }
 
 impl From<InnerWindow> for Window {
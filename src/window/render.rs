@@ -1,6 +1,7 @@
-use super::borders::{BordersElement, BORDER_SIZE};
-use super::decorations::{DecorationsElement, DECORATIONS_HEIGHT};
-use super::Window;
+use super::borders::BordersElement;
+use super::decorations::DecorationsElement;
+use super::{Window, WindowState};
+use crate::theme::ThemeColors;
 use smithay::backend::renderer::element::surface::WaylandSurfaceRenderElement;
 use smithay::backend::renderer::element::AsRenderElements;
 use smithay::backend::renderer::{ImportAll, ImportMem, Renderer, Texture};
@@ -14,6 +15,28 @@ impl Window {
     pub fn geometry(&self) -> Rectangle<i32, Logical> {
         SpaceElement::geometry(self)
     }
+    /// Whether the title bar is drawn, fullscreen windows don't have one
+    pub fn has_decorations(&self) -> bool {
+        self.decorations().is_some() && !self.state().contains(WindowState::FULLSCREEN)
+    }
+    /// Whether the borders are drawn, unless they are transparent or the window is fullscreen
+    pub fn has_borders(&self) -> bool {
+        self.config().theme().border_width > 0
+            && self.colors().border.0[3] > 0.0
+            && !self.state().contains(WindowState::FULLSCREEN)
+    }
+    /// Height of the title bar, 0 when it isn't drawn
+    pub fn title_height(&self) -> i32 {
+        if self.has_decorations() {
+            self.config().theme().title_height as i32
+        } else {
+            0
+        }
+    }
+    /// Colors of the borders and title bar, from the theme and the state of the window
+    pub fn colors(&self) -> ThemeColors {
+        *self.config().theme().colors(self)
+    }
 }
 
 impl IsAlive for Window {
@@ -25,39 +48,33 @@ impl IsAlive for Window {
 impl SpaceElement for Window {
     fn geometry(&self) -> Rectangle<i32, Logical> {
         let mut geometry = self.inner().geometry();
-        if self.decorations().is_some() {
-            geometry.size.h += DECORATIONS_HEIGHT as i32;
-        };
+        geometry.size.h += self.title_height();
         geometry
     }
 
     fn bbox(&self) -> Rectangle<i32, Logical> {
         let mut bbox = self.inner().bbox();
-        if self.decorations().is_some() {
-            bbox.size.h += DECORATIONS_HEIGHT as i32;
-        }
-        if self.is_focused() {
-            bbox.loc.x -= BORDER_SIZE as i32;
-            bbox.loc.y -= BORDER_SIZE as i32;
-            bbox.size.w += 2 * BORDER_SIZE as i32;
-            bbox.size.h += 2 * BORDER_SIZE as i32;
+        bbox.size.h += self.title_height();
+        if self.has_borders() {
+            let border_size = self.config().theme().border_width as i32;
+            bbox.loc.x -= border_size;
+            bbox.loc.y -= border_size;
+            bbox.size.w += 2 * border_size;
+            bbox.size.h += 2 * border_size;
         }
         bbox
     }
 
     fn is_in_input_region(&self, point: &Point<f64, Logical>) -> bool {
-        if self.is_focused() && !self.borders().get_edge(point).is_empty() {
+        if self.has_borders() && !self.borders().get_edge(point).is_empty() {
             return true;
         }
-        if self.decorations().is_some() && point.y < DECORATIONS_HEIGHT as f64 {
+        let title_height = self.title_height() as f64;
+        if point.y < title_height {
             return true;
         }
-        if self.decorations().is_some() {
-            self.inner()
-                .is_in_input_region(&(*point - Point::from((0.0, DECORATIONS_HEIGHT as f64))))
-        } else {
-            self.inner().is_in_input_region(point)
-        }
+        self.inner()
+            .is_in_input_region(&(*point - Point::from((0.0, title_height))))
     }
 
     fn z_index(&self) -> u8 {
@@ -70,10 +87,20 @@ impl SpaceElement for Window {
 
     fn output_enter(&self, output: &Output, overlap: Rectangle<i32, Logical>) {
         self.inner().output_enter(output, overlap);
+        {
+            let mut outputs = self.outputs_mut();
+            if outputs.contains(output) {
+                return;
+            }
+            outputs.push(output.clone());
+        }
+        self.send_scale();
     }
 
     fn output_leave(&self, output: &Output) {
         self.inner().output_leave(output);
+        self.outputs_mut().retain(|known| known != output);
+        self.send_scale();
     }
 
     fn refresh(&self) {
@@ -85,14 +112,14 @@ render_elements! {
     pub WindowElement<R> where R: ImportAll + ImportMem;
     Window=WaylandSurfaceRenderElement<R>,
     // DecorationsAndBorders=SolidColorRenderElement,
-    Decorations=DecorationsElement,
+    Decorations=DecorationsElement<R>,
     Borders=BordersElement,
 }
 
 impl<R> AsRenderElements<R> for Window
 where
     R: Renderer + ImportAll + ImportMem,
-    <R as Renderer>::TextureId: Clone + Texture + 'static,
+    <R as Renderer>::TextureId: Send + Clone + Texture + 'static,
 {
     type RenderElement = WindowElement<R>;
 
@@ -106,6 +133,7 @@ where
         let decorations = self
             .decorations()
             .as_ref()
+            .filter(|_| self.has_decorations())
             .map(|d| {
                 d.render_elements(renderer, location, scale, alpha)
                     .into_iter()
@@ -114,7 +142,7 @@ where
             })
             .unwrap_or_default();
         let borders = self
-            .is_focused()
+            .has_borders()
             .then(|| {
                 self.borders()
                     .render_elements(renderer, location, scale, alpha)
@@ -124,9 +152,7 @@ where
             })
             .unwrap_or_default();
 
-        if self.decorations().is_some() {
-            location.y += (scale.y * DECORATIONS_HEIGHT as f64) as i32;
-        }
+        location.y += (scale.y * self.title_height() as f64).round() as i32;
 
         let window = self
             .inner()
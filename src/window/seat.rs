@@ -1,20 +1,122 @@
-use super::decorations::{self, DECORATIONS_HEIGHT};
+use super::decorations::Button;
 use super::{Window, WindowState};
+use crate::input::KeyModifiers;
 use crate::utils::Edge;
 use crate::PocoWM;
 use smithay::backend::input::{ButtonState, KeyState};
-use smithay::input::keyboard::{KeyboardTarget, Keysym, KeysymHandle};
-use smithay::input::pointer::{self, PointerTarget};
+use smithay::input::keyboard::{KeyboardTarget, KeysymHandle};
+use smithay::input::pointer::{self, CursorImageStatus, PointerTarget};
 use smithay::input::touch::TouchTarget;
 use smithay::input::Seat;
 use smithay::input::{keyboard, touch};
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::Serial;
+use smithay::utils::{Logical, Point, Serial};
 use smithay::wayland::seat::WaylandFocus;
 use std::borrow::Cow;
 
 // const RESIZE_GRAB_SIZE: u32 = BUTTON_GAP;
 const RESIZE_GRAB_SIZE: u32 = 0;
+/// Longest time between two clicks on the title bar making a double click, in milliseconds
+const DOUBLE_CLICK_TIME: u32 = 400;
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+
+impl Window {
+    /// Location of the pointer relative to the window geometry, while it is over the window
+    pub fn pointer_location(&self) -> Option<Point<f64, Logical>> {
+        let location = self.seat_data().pointer_location?;
+        Some(location - self.geometry().loc.to_f64())
+    }
+
+    /// Title bar button under `loc`, `Some(None)` for the rest of the title bar and `None` off it
+    fn title_button(&self, loc: Point<f64, Logical>) -> Option<Option<Button>> {
+        self.decorations()
+            .as_ref()
+            .filter(|_| self.has_decorations() && loc.y < self.title_height() as f64)
+            .map(|decorations| decorations.get_button(loc))
+    }
+
+    /// Runs the action of a title bar button, once pressed and released over it
+    fn click_button(&self, button: Button, data: &mut PocoWM) {
+        match button {
+            Button::Close => {
+                self.toplevel().map(|t| t.send_close());
+            }
+            Button::Maximize => self.queue_toggle_maximize(data),
+            Button::Minimize => {
+                if let Some(xdg) = self.toplevel().cloned() {
+                    data.loop_handle.insert_idle(move |data| {
+                        data.xdg_minimize_request(&xdg);
+                    });
+                }
+            }
+        }
+    }
+
+    /// Records a left click on the title bar at `time`, returns whether it ends a double click
+    fn title_double_click(&self, time: u32) -> bool {
+        let mut seat_data = self.seat_data_mut();
+        let previous = seat_data.last_title_click.take();
+        if previous.is_some_and(|previous| time.wrapping_sub(previous) <= DOUBLE_CLICK_TIME) {
+            return true;
+        }
+        seat_data.last_title_click = Some(time);
+        false
+    }
+
+    /// Forwards a pointer motion to the client surface, which the pointer leaves over the title
+    /// bar and enters again below it
+    fn surface_motion(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::MotionEvent) {
+        let title_height = self.title_height() as f64;
+        let on_title_bar = event.location.y < title_height;
+        let entered = {
+            let mut seat_data = self.seat_data_mut();
+            seat_data.pointer_location = Some(event.location);
+            std::mem::replace(&mut seat_data.pointer_in_surface, !on_title_bar)
+        };
+        let Some(wl_surface) = self.wl_surface() else {
+            return;
+        };
+        if on_title_bar {
+            if entered {
+                PointerTarget::<PocoWM>::leave(
+                    wl_surface.as_ref(),
+                    seat,
+                    data,
+                    event.serial,
+                    event.time,
+                );
+            }
+            // The title bar is drawn by the compositor, so is its cursor
+            data.renderer.cursor.status = CursorImageStatus::default_named();
+            return;
+        }
+        let mut event = event.clone();
+        event.location.y -= title_height;
+        if entered {
+            PointerTarget::<PocoWM>::motion(wl_surface.as_ref(), seat, data, &event);
+        } else {
+            PointerTarget::<PocoWM>::enter(wl_surface.as_ref(), seat, data, &event);
+        }
+    }
+
+    /// Maximizes the window or restores it, once the pointer event is handled
+    fn queue_toggle_maximize(&self, data: &mut PocoWM) {
+        let Some(xdg) = self.toplevel().cloned() else {
+            return;
+        };
+        let is_maximized = self.state().contains(WindowState::MAXIMIZED);
+        data.loop_handle.insert_idle(move |data| {
+            if is_maximized {
+                data.xdg_unmaximize_request(&xdg);
+            } else {
+                data.xdg_maximize_request(&xdg);
+            }
+        });
+    }
+}
 
 impl WaylandFocus for Window {
     #[inline]
@@ -25,31 +127,11 @@ impl WaylandFocus for Window {
 
 impl PointerTarget<PocoWM> for Window {
     fn enter(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::MotionEvent) {
-        self.seat_data_mut().pointer_location = Some(event.location);
-        if self.decorations().is_some() && event.location.y < DECORATIONS_HEIGHT as f64 {
-            return;
-        }
-        if let Some(wl_surface) = self.wl_surface() {
-            let mut event = event.clone();
-            if self.decorations().is_some() {
-                event.location.y -= DECORATIONS_HEIGHT as f64;
-            }
-            PointerTarget::<PocoWM>::enter(wl_surface.as_ref(), seat, data, &event);
-        }
+        self.surface_motion(seat, data, event);
     }
 
     fn motion(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::MotionEvent) {
-        self.seat_data_mut().pointer_location = Some(event.location);
-        if self.decorations().is_some() && event.location.y < DECORATIONS_HEIGHT as f64 {
-            return;
-        }
-        if let Some(wl_surface) = self.wl_surface() {
-            let mut event = event.clone();
-            if self.decorations().is_some() {
-                event.location.y -= DECORATIONS_HEIGHT as f64;
-            }
-            PointerTarget::<PocoWM>::motion(wl_surface.as_ref(), seat, data, &event);
-        }
+        self.surface_motion(seat, data, event);
     }
 
     fn relative_motion(
@@ -64,14 +146,25 @@ impl PointerTarget<PocoWM> for Window {
     }
 
     fn button(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, event: &pointer::ButtonEvent) {
-        let Some(mut loc) = self.seat_data().pointer_location else {
+        let Some(loc) = self.pointer_location() else {
             return;
         };
-        loc = loc - self.geometry().loc.to_f64();
 
-        if event.state == ButtonState::Pressed && data.pressed_keys.contains(&Keysym::Alt_L) {
-            const BTN_LEFT: u32 = 0x110;
-            const BTN_RIGHT: u32 = 0x111;
+        // Title bar buttons are clicked only when released over the button they were pressed on
+        if event.state == ButtonState::Released && event.button == BTN_LEFT {
+            let pressed = self.seat_data_mut().pressed_button.take();
+            if let Some(pressed) = pressed {
+                if self.title_button(loc) == Some(Some(pressed)) {
+                    self.click_button(pressed, data);
+                }
+                return;
+            }
+        }
+
+        let modifier_pressed = data.seat.get_keyboard().is_some_and(|keyboard| {
+            KeyModifiers::from(&keyboard.modifier_state()).contains(data.config.general.modifier)
+        });
+        if event.state == ButtonState::Pressed && modifier_pressed {
             match event.button {
                 BTN_LEFT => {
                     if let Some(surface) = self.toplevel().cloned() {
@@ -124,54 +217,49 @@ impl PointerTarget<PocoWM> for Window {
             }
         }
 
-        if let Some(decorations) = self.decorations().as_ref() {
-            if loc.y < DECORATIONS_HEIGHT as f64 {
-                match decorations.get_button(loc) {
-                    Some(decorations::Button::Close) => {
-                        if event.state != ButtonState::Pressed {
-                            return;
-                        }
-                        self.toplevel().map(|t| t.send_close());
+        if let Some(button) = self.title_button(loc) {
+            match button {
+                Some(button) => {
+                    if event.state == ButtonState::Pressed && event.button == BTN_LEFT {
+                        self.seat_data_mut().pressed_button = Some(button);
                     }
-                    Some(decorations::Button::Maximize) => {
-                        if event.state != ButtonState::Pressed {
-                            return;
-                        }
-                        if let Some(xdg) = self.toplevel().cloned() {
-                            let is_maximized = self.state().contains(WindowState::MAXIMIZED);
-                            data.loop_handle.insert_idle(move |data| {
-                                if is_maximized {
-                                    data.xdg_unmaximize_request(&xdg);
-                                } else {
-                                    data.xdg_maximize_request(&xdg);
-                                }
-                            });
-                        }
+                }
+                None => {
+                    if event.state != ButtonState::Pressed {
+                        return;
                     }
-                    Some(decorations::Button::Minimize) => {
-                        if event.state != ButtonState::Pressed {
-                            return;
+                    match event.button {
+                        BTN_LEFT if self.title_double_click(event.time) => {
+                            self.queue_toggle_maximize(data);
                         }
-                        if let Some(xdg) = self.toplevel().cloned() {
-                            data.loop_handle.insert_idle(move |data| {
-                                data.xdg_minimize_request(&xdg);
-                            });
+                        BTN_LEFT => {
+                            if let Some(xdg) = self.toplevel() {
+                                // data.xdg_move_request(xdg, &seat.clone(), event.serial);
+                                let seat = seat.clone();
+                                let xdg = xdg.clone();
+                                let serial = event.serial;
+                                data.loop_handle.insert_idle(move |data| {
+                                    data.xdg_move_request(&xdg, &seat, serial);
+                                });
+                            }
                         }
-                    }
-                    None => {
-                        if let Some(xdg) = self.toplevel() {
-                            // data.xdg_move_request(xdg, &seat.clone(), event.serial);
-                            let seat = seat.clone();
-                            let xdg = xdg.clone();
-                            let serial = event.serial;
+                        BTN_MIDDLE => {
+                            self.toplevel().map(|t| t.send_close());
+                        }
+                        BTN_RIGHT => {
+                            let window = self.clone();
                             data.loop_handle.insert_idle(move |data| {
-                                data.xdg_move_request(&xdg, &seat, serial);
+                                let Some(pointer) = data.seat.get_pointer() else {
+                                    return;
+                                };
+                                data.open_window_menu(&window, pointer.current_location());
                             });
                         }
+                        _ => {}
                     }
                 }
-                return;
             }
+            return;
         }
         if let Some(wl_surface) = self.wl_surface() {
             PointerTarget::<PocoWM>::button(wl_surface.as_ref(), seat, data, event);
@@ -179,6 +267,9 @@ impl PointerTarget<PocoWM> for Window {
     }
 
     fn axis(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, frame: pointer::AxisFrame) {
+        if !self.seat_data().pointer_in_surface {
+            return;
+        }
         if let Some(wl_surface) = self.wl_surface() {
             PointerTarget::<PocoWM>::axis(wl_surface.as_ref(), seat, data, frame);
         }
@@ -191,7 +282,14 @@ impl PointerTarget<PocoWM> for Window {
     }
 
     fn leave(&self, seat: &Seat<PocoWM>, data: &mut PocoWM, serial: Serial, time: u32) {
-        self.seat_data_mut().pointer_location = None;
+        {
+            let mut seat_data = self.seat_data_mut();
+            seat_data.pointer_location = None;
+            seat_data.pressed_button = None;
+            if !std::mem::take(&mut seat_data.pointer_in_surface) {
+                return;
+            }
+        }
         if let Some(wl_surface) = self.wl_surface() {
             PointerTarget::<PocoWM>::leave(wl_surface.as_ref(), seat, data, serial, time);
         }
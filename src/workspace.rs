@@ -0,0 +1,386 @@
+use crate::action::Direction;
+use crate::handlers::xdg_shell::get_next_focus_id;
+use crate::ipc::protocol::Event;
+use crate::layout::{Id, Layout};
+use crate::window::Window;
+use crate::PocoWM;
+use anyhow::{anyhow, Result};
+use smithay::output::Output;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{Logical, Rectangle};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Name of the workspace shown at startup
+pub const DEFAULT_WORKSPACE: &str = "1";
+
+/// A workspace given to an action, by name or relative to the active one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceTarget {
+    Name(String),
+    Next,
+    Prev,
+}
+
+impl FromStr for WorkspaceTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "" => Err(anyhow!("Missing workspace name")),
+            "next" => Ok(Self::Next),
+            "prev" => Ok(Self::Prev),
+            name => Ok(Self::Name(name.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub name: String,
+    pub layout: Layout,
+}
+
+/// Every workspace but the active one, whose layout is `PocoWM::layout`. Each output shows one
+/// workspace, the active one being on the focused output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspaces {
+    pub active: String,
+    inactive: Vec<Workspace>,
+    /// Workspace each disabled output showed, by output name
+    previous: HashMap<String, String>,
+}
+
+impl Default for Workspaces {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_WORKSPACE.into(),
+            inactive: Vec::new(),
+            previous: HashMap::new(),
+        }
+    }
+}
+
+/// Numbered workspaces come first in numeric order, then named ones alphabetically
+fn compare_names(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u32>(), b.parse::<u32>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+impl Workspaces {
+    /// Names of every workspace, including the active one, in order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self
+            .inactive
+            .iter()
+            .map(|workspace| workspace.name.as_str())
+            .chain([self.active.as_str()])
+            .collect::<Vec<_>>();
+        names.sort_by(|a, b| compare_names(a, b));
+        names
+    }
+    pub fn resolve(&self, target: &WorkspaceTarget) -> String {
+        let offset = match target {
+            WorkspaceTarget::Name(name) => return name.clone(),
+            WorkspaceTarget::Next => 1,
+            WorkspaceTarget::Prev => -1,
+        };
+        let names = self.names();
+        let index = names
+            .iter()
+            .position(|name| *name == self.active)
+            .unwrap_or_default() as isize;
+        let index = (index + offset).rem_euclid(names.len() as isize);
+        names[index as usize].into()
+    }
+    /// Smallest number no workspace is named after
+    pub fn free_name(&self) -> String {
+        let names = self.names();
+        (1..)
+            .map(|i: u32| i.to_string())
+            .find(|name| !names.contains(&name.as_str()))
+            .unwrap_or_default()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Workspace> {
+        self.inactive.iter()
+    }
+    /// Inactive workspaces shown on an output
+    pub fn visible(&self) -> impl Iterator<Item = &Workspace> {
+        self.inactive
+            .iter()
+            .filter(|workspace| workspace.layout.output.is_some())
+    }
+    pub fn get(&self, name: &str) -> Option<&Workspace> {
+        self.inactive
+            .iter()
+            .find(|workspace| workspace.name == name)
+    }
+    pub fn get_on_output(&self, output: &Output) -> Option<&Workspace> {
+        self.visible()
+            .find(|workspace| workspace.layout.output.as_ref() == Some(output))
+    }
+    /// Removes the inactive workspace `name`, or makes a new one
+    pub fn take(&mut self, name: &str) -> Layout {
+        self.inactive
+            .iter()
+            .position(|workspace| workspace.name == name)
+            .map_or_else(Layout::new, |i| self.inactive.remove(i).layout)
+    }
+    /// Stores a workspace that isn't active anymore, dropping it if it is empty and hidden
+    pub fn insert(&mut self, name: String, layout: Layout) {
+        if layout.output.is_some() || layout.iter_windows().next().is_some() {
+            self.inactive.push(Workspace { name, layout });
+        }
+    }
+    /// Hides the workspace shown on `output`, returns its windows
+    pub fn remove_output(&mut self, output: &Output) -> Vec<Window> {
+        let Some(i) = self
+            .inactive
+            .iter()
+            .position(|workspace| workspace.layout.output.as_ref() == Some(output))
+        else {
+            return Vec::new();
+        };
+        let Workspace { name, mut layout } = self.inactive.remove(i);
+        layout.output = None;
+        self.previous.insert(output.name(), name.clone());
+        let windows = layout.iter_windows().cloned().collect();
+        self.insert(name, layout);
+        windows
+    }
+    /// Name of the workspace `output` showed before it was disabled, unless another output shows
+    /// it now
+    pub fn take_previous(&mut self, output: &Output) -> Option<String> {
+        let name = self.previous.remove(&output.name())?;
+        let shown = name == self.active
+            || self
+                .get(&name)
+                .is_some_and(|workspace| workspace.layout.output.is_some());
+        (!shown).then_some(name)
+    }
+    pub fn get_window_from_surface(&self, surface: &WlSurface) -> Option<&Window> {
+        self.inactive
+            .iter()
+            .find_map(|workspace| workspace.layout.get_window_from_surface(surface))
+    }
+    /// Removes a window of an inactive workspace, returning its former id
+    pub fn remove_window(&mut self, surface: &WlSurface) -> Option<(Id, Window)> {
+        let i = self
+            .inactive
+            .iter()
+            .position(|workspace| workspace.layout.get_window_from_surface(surface).is_some())?;
+        let layout = &mut self.inactive[i].layout;
+        let window = layout.get_window_from_surface(surface)?.clone();
+        let id = layout.get_window_id(&window)?;
+        layout.remove_element(id);
+        if layout.output.is_none() && layout.iter_windows().next().is_none() {
+            self.inactive.remove(i);
+        }
+        Some((id, window))
+    }
+}
+
+impl PocoWM {
+    /// Shows the workspace `target`, creating it if needed
+    pub fn switch_workspace(&mut self, target: &WorkspaceTarget) {
+        let name = self.workspaces.resolve(target);
+        if name == self.workspaces.active {
+            return;
+        }
+        let output = self
+            .workspaces
+            .get(&name)
+            .and_then(|workspace| workspace.layout.output.clone());
+        if let Some(output) = output {
+            self.focus_output(&output);
+            return;
+        }
+        self.layout.iter_windows().for_each(|window| {
+            window.unfocus();
+            self.renderer.unmap_elem(window);
+        });
+        let mut layout = self.workspaces.take(&name);
+        layout.output = self.layout.output.take();
+        let layout = std::mem::replace(&mut self.layout, layout);
+        let old_name = std::mem::replace(&mut self.workspaces.active, name.clone());
+        self.workspaces.insert(old_name, layout);
+        self.renderer.render(&self.layout);
+        self.focus_window(self.layout.get_last_focused_window(self.layout.root().id));
+        self.emit_ipc_event(Event::Workspace { name });
+    }
+
+    /// Moves the focused window to the workspace `target`, without following it
+    pub fn move_to_workspace(&mut self, target: &WorkspaceTarget) {
+        let name = self.workspaces.resolve(target);
+        if name == self.workspaces.active {
+            return;
+        }
+        let Some(id) = self
+            .focused_window()
+            .and_then(|w| self.layout.get_window_id(&w))
+        else {
+            return;
+        };
+        self.move_window_to_workspace(id, name);
+    }
+
+    /// Moves the element `id` of the active workspace to the workspace `name`
+    pub fn move_window_to_workspace(&mut self, id: Id, name: String) {
+        let focus_id = get_next_focus_id(&self.layout, id);
+        let Some(mut element) = self.layout.remove_element(id) else {
+            return;
+        };
+        element.set_weight(1.0);
+        element.get_window().map(|window| {
+            window.unfocus();
+            self.renderer.unmap_elem(window);
+        });
+
+        let mut layout = self.workspaces.take(&name);
+        // Floating windows keep their place relative to the output
+        let old_output = self.output_geometry(&self.layout);
+        let new_output = self.output_geometry(&layout);
+        if let (Some(old), Some(new), Some(window)) = (old_output, new_output, element.get_window())
+        {
+            window.floating_rect_mut().loc += new.loc - old.loc;
+        }
+        let root = layout.root().id;
+        let sibling = layout.get_last_focused_window(root).unwrap_or(root);
+        layout
+            .insert_element(sibling, element)
+            .map(|id| layout.on_focus(id));
+        if layout.output.is_some() {
+            self.renderer.render(&layout);
+        }
+        self.workspaces.insert(name, layout);
+
+        self.renderer.render(&self.layout);
+        self.focus_window(focus_id);
+    }
+
+    /// Moves the focused window to the output next to the focused one in `direction`
+    pub fn move_to_output(&mut self, direction: Direction) {
+        let name = self
+            .output_in_direction(direction)
+            .and_then(|output| self.workspaces.get_on_output(&output))
+            .map(|workspace| workspace.name.clone());
+        name.map(|name| self.move_to_workspace(&WorkspaceTarget::Name(name)));
+    }
+
+    /// Gives a workspace to a new output: the active one if no output had it yet, else the one
+    /// it showed before being disabled, else a new one
+    pub fn add_output(&mut self, output: &Output) {
+        if self.layout.output.is_none() {
+            self.layout.output = Some(output.clone());
+            self.renderer.render(&self.layout);
+            return;
+        }
+        let name = self
+            .workspaces
+            .take_previous(output)
+            .unwrap_or_else(|| self.workspaces.free_name());
+        let mut layout = self.workspaces.take(&name);
+        layout.output = Some(output.clone());
+        self.renderer.render(&layout);
+        self.workspaces.insert(name, layout);
+    }
+
+    /// Makes the workspace shown on `output` the active one, returns whether it changed
+    pub fn activate_output(&mut self, output: &Output) -> bool {
+        if self.layout.output.as_ref() == Some(output) {
+            return false;
+        }
+        let Some(name) = self
+            .workspaces
+            .get_on_output(output)
+            .map(|workspace| workspace.name.clone())
+        else {
+            return false;
+        };
+        self.layout.iter_windows().for_each(Window::unfocus);
+        let layout = std::mem::replace(&mut self.layout, self.workspaces.take(&name));
+        let old_name = std::mem::replace(&mut self.workspaces.active, name.clone());
+        self.workspaces.insert(old_name, layout);
+        self.emit_ipc_event(Event::Workspace { name });
+        true
+    }
+
+    /// Activates `output` and focuses the window that was focused last on it
+    pub fn focus_output(&mut self, output: &Output) {
+        if self.activate_output(output) {
+            self.focus_window(self.layout.get_last_focused_window(self.layout.root().id));
+        }
+    }
+
+    /// Focuses a window shown on any output, windows of hidden workspaces are left alone
+    pub fn focus_visible_window(&mut self, window: &Window) {
+        if self.layout.get_window_id(window).is_none() {
+            let Some(output) = self
+                .workspaces
+                .visible()
+                .find(|workspace| workspace.layout.get_window_id(window).is_some())
+                .and_then(|workspace| workspace.layout.output.clone())
+            else {
+                return;
+            };
+            self.activate_output(&output);
+        }
+        self.focus_window(self.layout.get_window_id(window));
+    }
+
+    /// Window of `surface` on any workspace, along with its id in the layout of that workspace
+    pub(crate) fn find_window(&self, surface: &WlSurface) -> Option<(Id, Window)> {
+        std::iter::once(&self.layout)
+            .chain(self.workspaces.iter().map(|workspace| &workspace.layout))
+            .find_map(|layout| {
+                let window = layout.get_window_from_surface(surface)?;
+                Some((layout.get_window_id(window)?, window.clone()))
+            })
+    }
+
+    /// Renders the layouts of every output
+    pub fn render_outputs(&mut self) {
+        self.renderer.render(&self.layout);
+        self.workspaces.visible().for_each(|workspace| {
+            self.renderer.render(&workspace.layout);
+        });
+    }
+
+    fn output_geometry(&self, layout: &Layout) -> Option<Rectangle<i32, Logical>> {
+        let output = self.renderer.layout_output(layout)?;
+        self.renderer.output_geometry(&output)
+    }
+
+    /// Closest output from the focused one in `direction`
+    pub fn output_in_direction(&self, direction: Direction) -> Option<Output> {
+        let current = self.output_geometry(&self.layout)?;
+        let center = |rect: Rectangle<i32, Logical>| {
+            let rect = rect.to_f64();
+            (
+                rect.loc.x + rect.size.w / 2.0,
+                rect.loc.y + rect.size.h / 2.0,
+            )
+        };
+        let (x, y) = center(current);
+        self.renderer
+            .outputs()
+            .filter_map(|output| Some((output, self.renderer.output_geometry(output)?)))
+            .filter(|(_, rect)| match direction {
+                Direction::Left => rect.loc.x + rect.size.w <= current.loc.x,
+                Direction::Right => rect.loc.x >= current.loc.x + current.size.w,
+                Direction::Up => rect.loc.y + rect.size.h <= current.loc.y,
+                Direction::Down => rect.loc.y >= current.loc.y + current.size.h,
+            })
+            .map(|(output, rect)| {
+                let (other_x, other_y) = center(rect);
+                (output, (other_x - x).powi(2) + (other_y - y).powi(2))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(output, _)| output.clone())
+    }
+}
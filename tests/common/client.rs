@@ -0,0 +1,514 @@
+use std::fs::File;
+use std::os::fd::AsFd as _;
+use std::os::unix::net::UnixStream;
+use wayland_client::protocol::wl_buffer::WlBuffer;
+use wayland_client::protocol::wl_callback::{self, WlCallback};
+use wayland_client::protocol::wl_compositor::WlCompositor;
+use wayland_client::protocol::wl_keyboard::{self, WlKeyboard};
+use wayland_client::protocol::wl_pointer::{self, WlPointer};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::protocol::wl_seat::{self, WlSeat};
+use wayland_client::protocol::wl_shm::{self, WlShm};
+use wayland_client::protocol::wl_shm_pool::WlShmPool;
+use wayland_client::protocol::wl_surface::WlSurface;
+use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, QueueHandle, WEnum};
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1;
+use wayland_protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::{
+    self, WpFractionalScaleV1,
+};
+use wayland_protocols::xdg::shell::client::xdg_surface::{self, XdgSurface};
+use wayland_protocols::xdg::shell::client::xdg_toplevel::{self, XdgToplevel};
+use wayland_protocols::xdg::shell::client::xdg_wm_base::{self, XdgWmBase};
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_shell_v1::{self, ZwlrLayerShellV1};
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::{
+    self, ZwlrLayerSurfaceV1,
+};
+
+const DEFAULT_SIZE: (i32, i32) = (100, 100);
+
+/// A scripted xdg toplevel, drawing a transparent buffer of the configured size
+#[derive(Debug)]
+pub struct Toplevel {
+    pub surface: WlSurface,
+    pub xdg_surface: XdgSurface,
+    pub xdg_toplevel: XdgToplevel,
+    pub configured_size: (i32, i32),
+    pub states: Vec<xdg_toplevel::State>,
+    pub configure_count: usize,
+    pub closed: bool,
+    /// Last preferred scale sent by wp-fractional-scale, in 120ths
+    pub preferred_scale: Option<u32>,
+    pending_size: (i32, i32),
+    pending_states: Vec<xdg_toplevel::State>,
+}
+
+/// A scripted layer surface on the top layer, drawing a transparent buffer of the configured size
+#[derive(Debug)]
+pub struct Layer {
+    pub surface: WlSurface,
+    pub layer_surface: ZwlrLayerSurfaceV1,
+    pub configured_size: (i32, i32),
+    pub closed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ClientState {
+    compositor: Option<WlCompositor>,
+    shm: Option<WlShm>,
+    wm_base: Option<XdgWmBase>,
+    seat: Option<WlSeat>,
+    pointer: Option<WlPointer>,
+    keyboard: Option<WlKeyboard>,
+    fractional_scale_manager: Option<WpFractionalScaleManagerV1>,
+    layer_shell: Option<ZwlrLayerShellV1>,
+    pub toplevels: Vec<Toplevel>,
+    pub layers: Vec<Layer>,
+    /// Surface the pointer is over, between wl_pointer enter and leave
+    pub pointer_focus: Option<WlSurface>,
+    /// Keys the client was told are pressed, as evdev codes
+    pub pressed_keys: Vec<u32>,
+    sync_done: bool,
+}
+
+pub struct TestClient {
+    connection: Connection,
+    queue: EventQueue<ClientState>,
+    qh: QueueHandle<ClientState>,
+    pub state: ClientState,
+}
+
+impl TestClient {
+    pub fn new(stream: UnixStream) -> Self {
+        let connection = Connection::from_socket(stream).expect("Failed to connect client");
+        let queue = connection.new_event_queue();
+        let qh = queue.handle();
+        connection.display().get_registry(&qh, ());
+        Self {
+            connection,
+            queue,
+            qh,
+            state: ClientState::default(),
+        }
+    }
+
+    /// Sends pending requests and handles every event already received, without blocking
+    pub fn pump(&mut self) {
+        let _ = self.queue.flush();
+        if let Some(guard) = self.queue.prepare_read() {
+            let _ = guard.read();
+        }
+        self.queue
+            .dispatch_pending(&mut self.state)
+            .expect("Failed to dispatch client events");
+        let _ = self.queue.flush();
+    }
+
+    /// Starts a `wl_display.sync`, [`Self::sync_done`] tells when the server answered
+    pub fn sync(&mut self) {
+        self.state.sync_done = false;
+        self.connection.display().sync(&self.qh, ());
+    }
+
+    pub fn sync_done(&self) -> bool {
+        self.state.sync_done
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.state.compositor.is_some() && self.state.shm.is_some() && self.state.wm_base.is_some()
+    }
+
+    /// Creates a toplevel and returns its index in [`ClientState::toplevels`]
+    pub fn create_toplevel(&mut self, app_id: &str) -> usize {
+        let compositor = self.state.compositor.as_ref().expect("No wl_compositor");
+        let wm_base = self.state.wm_base.as_ref().expect("No xdg_wm_base");
+        let index = self.state.toplevels.len();
+        let surface = compositor.create_surface(&self.qh, ());
+        let xdg_surface = wm_base.get_xdg_surface(&surface, &self.qh, index);
+        let xdg_toplevel = xdg_surface.get_toplevel(&self.qh, index);
+        xdg_toplevel.set_app_id(app_id.into());
+        xdg_toplevel.set_title(app_id.into());
+        if let Some(manager) = self.state.fractional_scale_manager.as_ref() {
+            manager.get_fractional_scale(&surface, &self.qh, index);
+        }
+        surface.commit();
+        self.state.toplevels.push(Toplevel {
+            surface,
+            xdg_surface,
+            xdg_toplevel,
+            configured_size: (0, 0),
+            states: Vec::new(),
+            configure_count: 0,
+            closed: false,
+            preferred_scale: None,
+            pending_size: (0, 0),
+            pending_states: Vec::new(),
+        });
+        index
+    }
+
+    pub fn destroy_toplevel(&mut self, index: usize) {
+        let toplevel = &self.state.toplevels[index];
+        toplevel.xdg_toplevel.destroy();
+        toplevel.xdg_surface.destroy();
+        toplevel.surface.destroy();
+    }
+
+    pub fn toplevel(&self, index: usize) -> &Toplevel {
+        &self.state.toplevels[index]
+    }
+
+    /// Creates a layer surface on the top layer of the focused output, returns its index in
+    /// [`ClientState::layers`]
+    pub fn create_layer(
+        &mut self,
+        namespace: &str,
+        anchor: zwlr_layer_surface_v1::Anchor,
+        size: (u32, u32),
+        exclusive_zone: i32,
+        keyboard_interactivity: zwlr_layer_surface_v1::KeyboardInteractivity,
+    ) -> usize {
+        let compositor = self.state.compositor.as_ref().expect("No wl_compositor");
+        let layer_shell = self
+            .state
+            .layer_shell
+            .as_ref()
+            .expect("No zwlr_layer_shell_v1");
+        let index = self.state.layers.len();
+        let surface = compositor.create_surface(&self.qh, ());
+        let layer_surface = layer_shell.get_layer_surface(
+            &surface,
+            None,
+            zwlr_layer_shell_v1::Layer::Top,
+            namespace.into(),
+            &self.qh,
+            index,
+        );
+        layer_surface.set_anchor(anchor);
+        layer_surface.set_size(size.0, size.1);
+        layer_surface.set_exclusive_zone(exclusive_zone);
+        layer_surface.set_keyboard_interactivity(keyboard_interactivity);
+        surface.commit();
+        self.state.layers.push(Layer {
+            surface,
+            layer_surface,
+            configured_size: (0, 0),
+            closed: false,
+        });
+        index
+    }
+
+    pub fn destroy_layer(&mut self, index: usize) {
+        let layer = &self.state.layers[index];
+        layer.layer_surface.destroy();
+        layer.surface.destroy();
+    }
+
+    pub fn layer(&self, index: usize) -> &Layer {
+        &self.state.layers[index]
+    }
+}
+
+impl ClientState {
+    fn draw(&mut self, index: usize, qh: &QueueHandle<Self>) {
+        let toplevel = &self.toplevels[index];
+        let name = format!("toplevel-{}", index);
+        self.attach_buffer(&toplevel.surface, toplevel.configured_size, &name, qh);
+    }
+
+    fn draw_layer(&mut self, index: usize, qh: &QueueHandle<Self>) {
+        let layer = &self.layers[index];
+        let name = format!("layer-{}", index);
+        self.attach_buffer(&layer.surface, layer.configured_size, &name, qh);
+    }
+
+    /// Attaches a transparent buffer of `size`, or of the default size where it is 0, then
+    /// commits
+    fn attach_buffer(
+        &self,
+        surface: &WlSurface,
+        (mut w, mut h): (i32, i32),
+        name: &str,
+        qh: &QueueHandle<Self>,
+    ) {
+        let Some(shm) = self.shm.as_ref() else {
+            return;
+        };
+        if w <= 0 {
+            w = DEFAULT_SIZE.0;
+        }
+        if h <= 0 {
+            h = DEFAULT_SIZE.1;
+        }
+        let stride = w * 4;
+        let size = stride * h;
+        let path = std::env::temp_dir().join(format!(
+            "pocowm-test-buffer-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .expect("Failed to create shm file");
+        let _ = std::fs::remove_file(&path);
+        file.set_len(size as u64).expect("Failed to size shm file");
+        let pool = shm.create_pool(file.as_fd(), size, qh, ());
+        let buffer = pool.create_buffer(0, w, h, stride, wl_shm::Format::Argb8888, qh, ());
+        pool.destroy();
+        surface.attach(Some(&buffer), 0, 0);
+        surface.damage_buffer(0, 0, w, h);
+        surface.commit();
+    }
+}
+
+impl Dispatch<WlRegistry, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        else {
+            return;
+        };
+        match interface.as_str() {
+            "wl_compositor" => {
+                state.compositor = Some(registry.bind(name, version.min(5), qh, ()));
+            }
+            "wl_shm" => {
+                state.shm = Some(registry.bind(name, 1, qh, ()));
+            }
+            "xdg_wm_base" => {
+                state.wm_base = Some(registry.bind(name, version.min(5), qh, ()));
+            }
+            "wl_seat" => {
+                state.seat = Some(registry.bind(name, version.min(7), qh, ()));
+            }
+            "zwlr_layer_shell_v1" => {
+                state.layer_shell = Some(registry.bind(name, version.min(4), qh, ()));
+            }
+            "wp_fractional_scale_manager_v1" => {
+                state.fractional_scale_manager = Some(registry.bind(name, 1, qh, ()));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlSeat, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        seat: &WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        else {
+            return;
+        };
+        if capabilities.contains(wl_seat::Capability::Pointer) && state.pointer.is_none() {
+            state.pointer = Some(seat.get_pointer(qh, ()));
+        }
+        if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none() {
+            state.keyboard = Some(seat.get_keyboard(qh, ()));
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlPointer,
+        event: wl_pointer::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_pointer::Event::Enter { surface, .. } => {
+                state.pointer_focus = Some(surface);
+            }
+            wl_pointer::Event::Leave { .. } => {
+                state.pointer_focus = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Enter { keys, .. } => {
+                state.pressed_keys = keys
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+            }
+            wl_keyboard::Event::Leave { .. } => {
+                state.pressed_keys.clear();
+            }
+            wl_keyboard::Event::Key {
+                key,
+                state: WEnum::Value(key_state),
+                ..
+            } => match key_state {
+                wl_keyboard::KeyState::Pressed => state.pressed_keys.push(key),
+                _ => state.pressed_keys.retain(|pressed| *pressed != key),
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        wm_base: &XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        xdg_surface: &XdgSurface,
+        event: xdg_surface::Event,
+        index: &usize,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let xdg_surface::Event::Configure { serial } = event else {
+            return;
+        };
+        xdg_surface.ack_configure(serial);
+        let toplevel = &mut state.toplevels[*index];
+        toplevel.configured_size = toplevel.pending_size;
+        toplevel.states = std::mem::take(&mut toplevel.pending_states);
+        toplevel.configure_count += 1;
+        state.draw(*index, qh);
+    }
+}
+
+impl Dispatch<XdgToplevel, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &XdgToplevel,
+        event: xdg_toplevel::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let toplevel = &mut state.toplevels[*index];
+        match event {
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                toplevel.pending_size = (width, height);
+                toplevel.pending_states = states
+                    .chunks_exact(4)
+                    .filter_map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]).try_into().ok())
+                    .collect();
+            }
+            xdg_toplevel::Event::Close => {
+                toplevel.closed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlCallback, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WlCallback,
+        event: wl_callback::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_callback::Event::Done { .. } = event {
+            state.sync_done = true;
+        }
+    }
+}
+
+impl Dispatch<ZwlrLayerSurfaceV1, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        layer_surface: &ZwlrLayerSurfaceV1,
+        event: zwlr_layer_surface_v1::Event,
+        index: &usize,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_layer_surface_v1::Event::Configure {
+                serial,
+                width,
+                height,
+            } => {
+                layer_surface.ack_configure(serial);
+                state.layers[*index].configured_size = (width as i32, height as i32);
+                state.draw_layer(*index, qh);
+            }
+            zwlr_layer_surface_v1::Event::Closed => {
+                state.layers[*index].closed = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WpFractionalScaleV1, usize> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &WpFractionalScaleV1,
+        event: wp_fractional_scale_v1::Event,
+        index: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wp_fractional_scale_v1::Event::PreferredScale { scale } = event {
+            state.toplevels[*index].preferred_scale = Some(scale);
+        }
+    }
+}
+
+delegate_noop!(ClientState: ignore WlCompositor);
+delegate_noop!(ClientState: ignore WlSurface);
+delegate_noop!(ClientState: ignore WlShm);
+delegate_noop!(ClientState: ignore WlShmPool);
+delegate_noop!(ClientState: ignore WlBuffer);
+delegate_noop!(ClientState: ignore WpFractionalScaleManagerV1);
+delegate_noop!(ClientState: ignore ZwlrLayerShellV1);
@@ -0,0 +1,154 @@
+use smithay::backend::input::{
+    AbsolutePositionEvent, ButtonState, Device, DeviceCapability, Event, InputBackend, KeyState,
+    KeyboardKeyEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, UnusedEvent,
+};
+use smithay::input::keyboard::Keycode;
+use std::path::PathBuf;
+
+/// Input backend whose events are built by the tests themselves
+#[derive(Debug)]
+pub struct TestInput;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TestDevice;
+
+impl Device for TestDevice {
+    fn id(&self) -> String {
+        "pocowm-test".into()
+    }
+    fn name(&self) -> String {
+        "PocoWM test device".into()
+    }
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        matches!(
+            capability,
+            DeviceCapability::Keyboard | DeviceCapability::Pointer
+        )
+    }
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyEvent {
+    pub time: u64,
+    /// Linux evdev key code, as in `linux/input-event-codes.h`
+    pub key: u32,
+    pub state: KeyState,
+}
+
+impl Event<TestInput> for KeyEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn device(&self) -> TestDevice {
+        TestDevice
+    }
+}
+
+impl KeyboardKeyEvent<TestInput> for KeyEvent {
+    fn key_code(&self) -> Keycode {
+        // xkb keycodes are offset by 8 from evdev ones
+        (self.key + 8).into()
+    }
+    fn state(&self) -> KeyState {
+        self.state
+    }
+    fn count(&self) -> u32 {
+        match self.state {
+            KeyState::Pressed => 1,
+            KeyState::Released => 0,
+        }
+    }
+}
+
+/// Absolute pointer motion, already expressed in output-local logical coordinates
+#[derive(Debug, Clone)]
+pub struct MotionAbsoluteEvent {
+    pub time: u64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Event<TestInput> for MotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn device(&self) -> TestDevice {
+        TestDevice
+    }
+}
+
+impl AbsolutePositionEvent<TestInput> for MotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+    fn y(&self) -> f64 {
+        self.y
+    }
+    fn x_transformed(&self, _width: i32) -> f64 {
+        self.x
+    }
+    fn y_transformed(&self, _height: i32) -> f64 {
+        self.y
+    }
+}
+
+impl PointerMotionAbsoluteEvent<TestInput> for MotionAbsoluteEvent {}
+
+#[derive(Debug, Clone)]
+pub struct ButtonEvent {
+    pub time: u64,
+    pub button: u32,
+    pub state: ButtonState,
+}
+
+impl Event<TestInput> for ButtonEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn device(&self) -> TestDevice {
+        TestDevice
+    }
+}
+
+impl PointerButtonEvent<TestInput> for ButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+impl InputBackend for TestInput {
+    type Device = TestDevice;
+    type KeyboardKeyEvent = KeyEvent;
+    type PointerAxisEvent = UnusedEvent;
+    type PointerButtonEvent = ButtonEvent;
+    type PointerMotionEvent = UnusedEvent;
+    type PointerMotionAbsoluteEvent = MotionAbsoluteEvent;
+    type GestureSwipeBeginEvent = UnusedEvent;
+    type GestureSwipeUpdateEvent = UnusedEvent;
+    type GestureSwipeEndEvent = UnusedEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+    type SwitchToggleEvent = UnusedEvent;
+    type SpecialEvent = UnusedEvent;
+}
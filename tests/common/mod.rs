@@ -0,0 +1,302 @@
+#![allow(dead_code)]
+
+pub mod client;
+pub mod input;
+
+use client::TestClient;
+use input::{ButtonEvent, KeyEvent, MotionAbsoluteEvent, TestInput};
+use pocowm::backends::headless::HeadlessOptions;
+use pocowm::window::Window;
+use pocowm::PocoWM;
+use smithay::backend::input::{ButtonState, InputEvent, KeyState};
+use smithay::output::Output;
+use smithay::utils::{Logical, Point, Rectangle};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::XdgToplevelSurfaceData;
+use std::io::{BufRead as _, BufReader, ErrorKind, Write as _};
+use std::os::unix::net::UnixStream;
+use std::sync::Once;
+use std::time::Duration;
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::{
+    Anchor, KeyboardInteractivity,
+};
+
+/// Evdev key codes, see `linux/input-event-codes.h`
+pub mod keys {
+    pub const KEY_ESC: u32 = 1;
+    pub const KEY_1: u32 = 2;
+    pub const KEY_2: u32 = 3;
+    pub const KEY_0: u32 = 11;
+    pub const KEY_MINUS: u32 = 12;
+    pub const KEY_EQUAL: u32 = 13;
+    pub const KEY_Q: u32 = 16;
+    pub const KEY_E: u32 = 18;
+    pub const KEY_U: u32 = 22;
+    pub const KEY_ENTER: u32 = 28;
+    pub const KEY_F: u32 = 33;
+    pub const KEY_H: u32 = 35;
+    pub const KEY_J: u32 = 36;
+    pub const KEY_K: u32 = 37;
+    pub const KEY_L: u32 = 38;
+    pub const KEY_LEFTSHIFT: u32 = 42;
+    pub const KEY_B: u32 = 48;
+    pub const KEY_N: u32 = 49;
+    pub const KEY_M: u32 = 50;
+    pub const KEY_LEFTALT: u32 = 56;
+}
+
+pub const BTN_LEFT: u32 = 0x110;
+pub const BTN_RIGHT: u32 = 0x111;
+pub const BTN_MIDDLE: u32 = 0x112;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// A headless PocoWM running inside the test process, with clients connected through
+/// private socket pairs
+pub struct Fixture {
+    pub server: PocoWM,
+    pub output: Output,
+    pub clients: Vec<TestClient>,
+    time: u64,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            // Keep the listening socket and the config away from the user's ones
+            let test_dir = std::env::temp_dir().join(format!("pocowm-test-{}", std::process::id()));
+            std::fs::create_dir_all(&test_dir).expect("Failed to create test dir");
+            std::env::set_var("XDG_RUNTIME_DIR", &test_dir);
+            std::env::set_var("XDG_CONFIG_HOME", test_dir.join("config"));
+        });
+
+        let mut server = PocoWM::new().expect("Failed to init PocoWM");
+        let output = server
+            .init_headless(HeadlessOptions {
+                size: (1000, 800).into(),
+                refresh: 60_000,
+                ..Default::default()
+            })
+            .expect("Failed to init headless backend");
+        Self {
+            server,
+            output,
+            clients: Vec::new(),
+            time: 0,
+        }
+    }
+
+    /// Adds another 1000x800 headless output at `position`
+    pub fn add_output(&mut self, position: Point<i32, Logical>) -> Output {
+        let output = self
+            .server
+            .init_headless(HeadlessOptions {
+                size: (1000, 800).into(),
+                refresh: 60_000,
+                position,
+            })
+            .expect("Failed to add headless output");
+        self.settle();
+        output
+    }
+
+    /// Connects a new client and returns its index in [`Self::clients`]
+    pub fn add_client(&mut self) -> usize {
+        let (client_stream, server_stream) = UnixStream::pair().expect("Failed to create socket");
+        self.server
+            .insert_client(server_stream)
+            .expect("Failed to insert client");
+        self.clients.push(TestClient::new(client_stream));
+        let index = self.clients.len() - 1;
+        self.roundtrip(index);
+        assert!(self.clients[index].is_ready(), "Missing globals");
+        index
+    }
+
+    pub fn client(&mut self, index: usize) -> &mut TestClient {
+        &mut self.clients[index]
+    }
+
+    pub fn dispatch(&mut self) {
+        self.server
+            .dispatch(Some(Duration::ZERO))
+            .expect("Failed to dispatch server");
+        self.clients.iter_mut().for_each(TestClient::pump);
+    }
+
+    /// Dispatches both sides until the server answered a `wl_display.sync` of the client
+    pub fn roundtrip(&mut self, client: usize) {
+        self.clients[client].sync();
+        for _ in 0..MAX_ITERATIONS {
+            self.dispatch();
+            if self.clients[client].sync_done() {
+                return;
+            }
+        }
+        panic!("Roundtrip timed out");
+    }
+
+    /// Roundtrips every client a few times, so configure/ack/commit sequences settle
+    pub fn settle(&mut self) {
+        for _ in 0..3 {
+            (0..self.clients.len()).for_each(|client| self.roundtrip(client));
+        }
+    }
+
+    /// Creates a toplevel on `client` and waits for it to be mapped
+    pub fn create_toplevel(&mut self, client: usize, app_id: &str) -> usize {
+        let toplevel = self.clients[client].create_toplevel(app_id);
+        self.settle();
+        toplevel
+    }
+
+    /// Creates a layer surface on `client` and waits for it to be mapped
+    pub fn create_layer(
+        &mut self,
+        client: usize,
+        namespace: &str,
+        anchor: Anchor,
+        size: (u32, u32),
+        exclusive_zone: i32,
+        keyboard_interactivity: KeyboardInteractivity,
+    ) -> usize {
+        let layer = self.clients[client].create_layer(
+            namespace,
+            anchor,
+            size,
+            exclusive_zone,
+            keyboard_interactivity,
+        );
+        self.settle();
+        layer
+    }
+
+    /// Finds a window by app_id, on any workspace
+    pub fn window(&self, app_id: &str) -> Window {
+        let hidden = self.server.workspaces().iter();
+        self.server
+            .layout()
+            .iter_windows()
+            .chain(hidden.flat_map(|workspace| workspace.layout.iter_windows()))
+            .find(|w| {
+                w.toplevel().is_some_and(|t| {
+                    with_states(t.wl_surface(), |states| {
+                        states
+                            .data_map
+                            .get::<XdgToplevelSurfaceData>()
+                            .and_then(|data| data.lock().ok()?.app_id.clone())
+                            .is_some_and(|id| id == app_id)
+                    })
+                })
+            })
+            .cloned()
+            .unwrap_or_else(|| panic!("No window with app_id {}", app_id))
+    }
+
+    pub fn window_rect(&self, app_id: &str) -> Option<Rectangle<i32, Logical>> {
+        let window = self.window(app_id);
+        self.server.renderer().element_geometry(&window)
+    }
+
+    pub fn focused(&self) -> Option<Window> {
+        self.server.focused_window()
+    }
+
+    fn next_time(&mut self) -> u64 {
+        self.time += 10_000;
+        self.time
+    }
+
+    pub fn key(&mut self, key: u32, state: KeyState) {
+        let time = self.next_time();
+        self.server.handle_input::<TestInput>(InputEvent::Keyboard {
+            event: KeyEvent { time, key, state },
+        });
+        self.settle();
+    }
+
+    /// Presses every key in order, then releases them in reverse order
+    pub fn key_combo(&mut self, keys: &[u32]) {
+        keys.iter()
+            .for_each(|key| self.key(*key, KeyState::Pressed));
+        keys.iter()
+            .rev()
+            .for_each(|key| self.key(*key, KeyState::Released));
+    }
+
+    pub fn pointer_move(&mut self, location: Point<f64, Logical>) {
+        let time = self.next_time();
+        self.server
+            .handle_input::<TestInput>(InputEvent::PointerMotionAbsolute {
+                event: MotionAbsoluteEvent {
+                    time,
+                    x: location.x,
+                    y: location.y,
+                },
+            });
+        self.settle();
+    }
+
+    pub fn button(&mut self, button: u32, state: ButtonState) {
+        let time = self.next_time();
+        self.server
+            .handle_input::<TestInput>(InputEvent::PointerButton {
+                event: ButtonEvent {
+                    time,
+                    button,
+                    state,
+                },
+            });
+        self.settle();
+    }
+
+    /// Connects to the IPC socket of the server
+    pub fn ipc_connect(&mut self) -> BufReader<UnixStream> {
+        let path = self.server.ipc_socket_path().expect("No IPC socket");
+        let stream = UnixStream::connect(path).expect("Failed to connect to IPC socket");
+        stream
+            .set_nonblocking(true)
+            .expect("Failed to init IPC stream");
+        self.dispatch();
+        BufReader::new(stream)
+    }
+
+    /// Reads the next JSON line sent by the server on an IPC connection
+    pub fn ipc_read(&mut self, stream: &mut BufReader<UnixStream>) -> serde_json::Value {
+        let mut line = String::new();
+        for _ in 0..MAX_ITERATIONS {
+            match stream.read_line(&mut line) {
+                Ok(_) if line.ends_with('\n') => {
+                    return serde_json::from_str(&line).expect("Invalid IPC message");
+                }
+                Ok(0) => panic!("IPC connection closed"),
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => panic!("Failed to read IPC message: {}", err),
+            }
+            self.dispatch();
+        }
+        panic!("IPC read timed out");
+    }
+
+    /// Sends a request on an IPC connection and waits for its response
+    pub fn ipc_request(
+        &mut self,
+        stream: &mut BufReader<UnixStream>,
+        request: serde_json::Value,
+    ) -> serde_json::Value {
+        let mut message = request.to_string();
+        message.push('\n');
+        stream
+            .get_mut()
+            .write_all(message.as_bytes())
+            .expect("Failed to send IPC request");
+        self.ipc_read(stream)
+    }
+
+    pub fn click(&mut self, button: u32) {
+        self.button(button, ButtonState::Pressed);
+        self.button(button, ButtonState::Released);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::keys::*;
+use common::Fixture;
+use pocowm::config::{Color, Config, KeyModifiers};
+use pocowm::window::WindowState;
+use smithay::backend::input::KeyState;
+use smithay::utils::Rectangle;
+use std::time::Duration;
+
+fn write_config(content: &str) {
+    let path = Config::path().expect("No config path");
+    std::fs::create_dir_all(path.parent().expect("No config dir")).expect("Failed to create dir");
+    std::fs::write(path, content).expect("Failed to write config");
+}
+
+#[test]
+fn colors_parse_with_and_without_alpha() {
+    assert_eq!(
+        "#ff0000".parse::<Color>().ok(),
+        Some(Color([1.0, 0.0, 0.0, 1.0]))
+    );
+    assert_eq!(
+        "#00ff0000".parse::<Color>().ok(),
+        Some(Color([0.0, 1.0, 0.0, 0.0]))
+    );
+    assert!("ff0000".parse::<Color>().is_err());
+    assert!("#ff00".parse::<Color>().is_err());
+    assert!("#gg0000".parse::<Color>().is_err());
+}
+
+#[test]
+fn key_modifiers_parse_names_joined_by_plus() {
+    assert_eq!(
+        "Alt+Shift".parse::<KeyModifiers>().ok(),
+        Some(KeyModifiers::ALT | KeyModifiers::SHIFT)
+    );
+    assert_eq!(
+        "ctrl + mod4".parse::<KeyModifiers>().ok(),
+        Some(KeyModifiers::CTRL | KeyModifiers::SUPER)
+    );
+    assert!("Alt+Hyper".parse::<KeyModifiers>().is_err());
+
+    let config: Config = toml::from_str("[general]\nmodifier = \"Super\"").expect("Invalid config");
+    assert_eq!(config.general.modifier, KeyModifiers::SUPER);
+}
+
+#[test]
+fn config_file_changes_are_reloaded_unless_invalid() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+
+    // Picked up by the watcher, which polls the file every second
+    write_config("[general]\ngap = 10\n");
+    for _ in 0..30 {
+        if fixture.server.config().general.gap == 10 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        fixture.dispatch();
+    }
+    fixture.settle();
+    assert_eq!(
+        fixture.window_rect("app"),
+        Some(Rectangle::new((10, 10).into(), (980, 780).into()))
+    );
+
+    write_config("[general]\ngap = \"wide\"\n");
+    assert!(fixture.server.reload_config().is_err());
+    assert_eq!(fixture.server.config().general.gap, 10);
+}
+
+#[test]
+fn release_bindings_keep_the_whole_key_from_the_client() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+    let config = Config::parse(
+        r#"
+        [general]
+        default_bindings = false
+
+        [[bindings]]
+        keys = "Alt+F"
+        action = "floating"
+        release = true
+        "#,
+    )
+    .expect("Invalid config");
+    fixture.server.apply_config(config);
+
+    fixture.key(KEY_LEFTALT, KeyState::Pressed);
+    fixture.key(KEY_F, KeyState::Pressed);
+    assert_eq!(fixture.client(client).state.pressed_keys, vec![KEY_LEFTALT]);
+    assert!(fixture.window("app").state().is_empty());
+
+    fixture.key(KEY_F, KeyState::Released);
+    fixture.key(KEY_LEFTALT, KeyState::Released);
+    assert!(fixture.client(client).state.pressed_keys.is_empty());
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::FLOATING));
+}
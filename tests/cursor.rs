@@ -0,0 +1,36 @@
+mod common;
+
+use common::keys::*;
+use common::{Fixture, BTN_LEFT};
+use smithay::backend::input::{ButtonState, KeyState};
+use smithay::input::pointer::CursorIcon;
+
+#[test]
+fn cursor_follows_the_pointer() {
+    let mut fixture = Fixture::new();
+
+    fixture.pointer_move((300.0, 200.0).into());
+
+    let cursor = &fixture.server.renderer().cursor;
+    assert_eq!(cursor.location, (300.0, 200.0).into());
+    assert_eq!(cursor.icon(), Some(CursorIcon::Default));
+}
+
+#[test]
+fn moving_a_window_shows_the_grabbing_cursor() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+    fixture.pointer_move((500.0, 400.0).into());
+
+    fixture.key(KEY_LEFTALT, KeyState::Pressed);
+    fixture.button(BTN_LEFT, ButtonState::Pressed);
+    assert_eq!(
+        fixture.server.renderer().cursor.icon(),
+        Some(CursorIcon::Grabbing)
+    );
+
+    fixture.button(BTN_LEFT, ButtonState::Released);
+    fixture.key(KEY_LEFTALT, KeyState::Released);
+    assert_eq!(fixture.server.renderer().cursor.grab_icon, None);
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::keys::*;
+use common::{Fixture, BTN_LEFT, BTN_MIDDLE, BTN_RIGHT};
+use pocowm::action::Action;
+use pocowm::window::decorations::Button;
+use pocowm::window::WindowState;
+use smithay::backend::input::ButtonState;
+
+/// A focused window with a title bar, the pointer being on the title bar
+fn fixture_with_title_bar() -> (Fixture, usize) {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "app");
+    fixture.server.run_action(Action::ToggleDecorations);
+    fixture.settle();
+    let rect = fixture.window_rect("app").expect("Window not mapped");
+    // Right of the buttons
+    let location = (rect.loc.x as f64 + 200.0, rect.loc.y as f64 + 10.0);
+    fixture.pointer_move(location.into());
+    (fixture, toplevel)
+}
+
+#[test]
+fn double_click_on_title_bar_toggles_maximize() {
+    let (mut fixture, _) = fixture_with_title_bar();
+
+    fixture.click(BTN_LEFT);
+    assert!(!fixture
+        .window("app")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+
+    fixture.click(BTN_LEFT);
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+}
+
+#[test]
+fn middle_click_on_title_bar_closes() {
+    let (mut fixture, toplevel) = fixture_with_title_bar();
+
+    fixture.click(BTN_MIDDLE);
+
+    assert!(fixture.client(0).toplevel(toplevel).closed);
+}
+
+#[test]
+fn right_click_on_title_bar_opens_window_menu() {
+    let (mut fixture, _) = fixture_with_title_bar();
+
+    fixture.click(BTN_RIGHT);
+    let menu = fixture.server.renderer().menu.as_ref();
+    assert_eq!(
+        menu.map(|menu| menu.window.clone()),
+        Some(fixture.window("app"))
+    );
+
+    // The menu opens under the pointer, its first entry floats the window
+    fixture.click(BTN_LEFT);
+
+    assert!(fixture.server.renderer().menu.is_none());
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::FLOATING));
+}
+
+#[test]
+fn window_menu_is_driven_by_the_keyboard() {
+    let (mut fixture, toplevel) = fixture_with_title_bar();
+    fixture.click(BTN_RIGHT);
+
+    // Float, Maximize, Hide title bar, Minimize, then Close
+    (0..5).for_each(|_| fixture.key_combo(&[KEY_J]));
+    fixture.key_combo(&[KEY_ENTER]);
+
+    assert!(fixture.server.renderer().menu.is_none());
+    assert!(fixture.client(0).toplevel(toplevel).closed);
+
+    fixture.click(BTN_RIGHT);
+    fixture.key_combo(&[KEY_ESC]);
+
+    assert!(fixture.server.renderer().menu.is_none());
+}
+
+/// Moves the pointer over the close button, the first one of the title bar
+fn move_to_close_button(fixture: &mut Fixture) {
+    let rect = fixture.window_rect("app").expect("Window not mapped");
+    let location = (rect.loc.x as f64 + 12.0, rect.loc.y as f64 + 12.0);
+    fixture.pointer_move(location.into());
+}
+
+#[test]
+fn close_button_fires_on_release() {
+    let (mut fixture, toplevel) = fixture_with_title_bar();
+    move_to_close_button(&mut fixture);
+
+    fixture.button(BTN_LEFT, ButtonState::Pressed);
+    assert_eq!(
+        fixture.window("app").seat_data().pressed_button,
+        Some(Button::Close)
+    );
+    assert!(!fixture.client(0).toplevel(toplevel).closed);
+
+    fixture.button(BTN_LEFT, ButtonState::Released);
+    assert!(fixture.client(0).toplevel(toplevel).closed);
+}
+
+#[test]
+fn releasing_off_the_pressed_button_cancels_the_click() {
+    let (mut fixture, toplevel) = fixture_with_title_bar();
+    move_to_close_button(&mut fixture);
+
+    fixture.button(BTN_LEFT, ButtonState::Pressed);
+    let rect = fixture.window_rect("app").expect("Window not mapped");
+    fixture.pointer_move((rect.loc.x as f64 + 200.0, rect.loc.y as f64 + 10.0).into());
+    fixture.button(BTN_LEFT, ButtonState::Released);
+
+    assert!(!fixture.client(0).toplevel(toplevel).closed);
+    assert_eq!(fixture.window("app").seat_data().pressed_button, None);
+}
+
+#[test]
+fn title_bar_takes_the_pointer_from_the_client() {
+    let (mut fixture, toplevel) = fixture_with_title_bar();
+    let surface = fixture.client(0).toplevel(toplevel).surface.clone();
+    assert_eq!(fixture.client(0).state.pointer_focus, None);
+
+    let rect = fixture.window_rect("app").expect("Window not mapped");
+    let below_title_bar = (rect.loc.x as f64 + 200.0, rect.loc.y as f64 + 100.0);
+    fixture.pointer_move(below_title_bar.into());
+    assert_eq!(fixture.client(0).state.pointer_focus, Some(surface.clone()));
+
+    fixture.pointer_move((rect.loc.x as f64 + 200.0, rect.loc.y as f64 + 10.0).into());
+    assert_eq!(fixture.client(0).state.pointer_focus, None);
+
+    fixture.pointer_move(below_title_bar.into());
+    assert_eq!(fixture.client(0).state.pointer_focus, Some(surface));
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::keys::*;
+use common::Fixture;
+use serde_json::json;
+use std::io::{BufRead as _, ErrorKind, Write as _};
+
+#[test]
+fn get_windows_lists_toplevels() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "get_windows" }));
+
+    assert_eq!(response["type"], "windows");
+    let windows = response["data"].as_array().unwrap();
+    assert_eq!(windows.len(), 2);
+    assert_eq!(windows[0]["app_id"], "left");
+    assert_eq!(windows[0]["focused"], false);
+    assert_eq!(windows[1]["app_id"], "right");
+    assert_eq!(windows[1]["title"], "right");
+    assert_eq!(windows[1]["focused"], true);
+    assert_eq!(windows[1]["state"], json!([]));
+    assert_eq!(
+        windows[1]["geometry"],
+        json!({ "x": 510, "y": 20, "width": 470, "height": 760 })
+    );
+}
+
+#[test]
+fn get_tree_returns_layout() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "get_tree" }));
+
+    assert_eq!(response["type"], "tree");
+    let root = &response["data"];
+    assert_eq!(root["type"], "container");
+    assert_eq!(root["id"], 0);
+    assert_eq!(root["layout_type"], "horizontal");
+    assert_eq!(
+        root["geometry"],
+        json!({ "x": 20, "y": 20, "width": 960, "height": 760 })
+    );
+    let children = root["children"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0]["type"], "window");
+    assert_eq!(children[0]["app_id"], "left");
+    assert_eq!(root["last_focused"], children[1]["id"]);
+}
+
+#[test]
+fn get_outputs_lists_headless_output() {
+    let mut fixture = Fixture::new();
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "get_outputs" }));
+
+    assert_eq!(response["type"], "outputs");
+    let outputs = response["data"].as_array().unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0]["name"], "headless");
+    assert_eq!(
+        outputs[0]["geometry"],
+        json!({ "x": 0, "y": 0, "width": 1000, "height": 800 })
+    );
+    assert_eq!(outputs[0]["refresh"], 60_000);
+}
+
+#[test]
+fn command_runs_action() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(
+        &mut ipc,
+        json!({ "type": "command", "command": "floating toggle" }),
+    );
+    fixture.settle();
+
+    assert_eq!(response, json!({ "type": "success" }));
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "get_windows" }));
+    assert_eq!(response["data"][0]["state"], json!(["floating"]));
+}
+
+#[test]
+fn invalid_requests_are_reported() {
+    let mut fixture = Fixture::new();
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "command", "command": "dance" }));
+    assert_eq!(response["type"], "error");
+
+    let response = fixture.ipc_request(&mut ipc, json!({ "type": "nothing" }));
+    assert_eq!(response["type"], "error");
+}
+
+#[test]
+fn subscribers_receive_events() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let mut ipc = fixture.ipc_connect();
+
+    let response = fixture.ipc_request(
+        &mut ipc,
+        json!({ "type": "subscribe", "events": ["window", "window_state"] }),
+    );
+    assert_eq!(response, json!({ "type": "success" }));
+
+    fixture.create_toplevel(client, "app");
+    let event = fixture.ipc_read(&mut ipc);
+    assert_eq!(event["event"], "window_created");
+    assert_eq!(event["data"]["app_id"], "app");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_F]);
+    let event = fixture.ipc_read(&mut ipc);
+    assert_eq!(event["event"], "window_state");
+    assert_eq!(event["data"]["state"], json!(["floating"]));
+}
+
+#[test]
+fn focus_events_follow_focus() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    let mut ipc = fixture.ipc_connect();
+    fixture.ipc_request(
+        &mut ipc,
+        json!({ "type": "subscribe", "events": ["focus"] }),
+    );
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+
+    let event = fixture.ipc_read(&mut ipc);
+    assert_eq!(event["event"], "focus");
+    assert_eq!(event["data"]["app_id"], "left");
+}
+
+#[test]
+fn clients_never_ending_a_request_are_disconnected() {
+    let mut fixture = Fixture::new();
+    let mut ipc = fixture.ipc_connect();
+
+    // Spaces without a newline, past the 1 MiB limit
+    let chunk = [b' '; 64 * 1024];
+    let mut written = 0;
+    for _ in 0..1000 {
+        match ipc.get_mut().write(&chunk) {
+            Ok(n) => written += n,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+            Err(_) => break,
+        }
+        fixture.dispatch();
+        if written > 2 << 20 {
+            break;
+        }
+    }
+
+    let mut line = String::new();
+    let mut closed = false;
+    for _ in 0..100 {
+        fixture.dispatch();
+        match ipc.read_line(&mut line) {
+            Ok(0) => closed = true,
+            Err(err) if err.kind() != ErrorKind::WouldBlock => closed = true,
+            _ => {}
+        }
+        if closed {
+            break;
+        }
+    }
+    assert!(closed);
+}
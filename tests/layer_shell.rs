@@ -0,0 +1,97 @@
+mod common;
+
+use common::Fixture;
+use smithay::utils::Rectangle;
+use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::{
+    Anchor, KeyboardInteractivity,
+};
+
+#[test]
+fn exclusive_zone_shrinks_the_layout() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+    let before = fixture.window_rect("window").expect("Window not mapped");
+
+    let layer = fixture.create_layer(
+        client,
+        "panel",
+        Anchor::Top | Anchor::Left | Anchor::Right,
+        (0, 30),
+        30,
+        KeyboardInteractivity::None,
+    );
+
+    assert_eq!(
+        fixture.client(client).layer(layer).configured_size,
+        (1000, 30)
+    );
+    assert_eq!(
+        fixture.window_rect("window"),
+        Some(Rectangle::new(
+            (before.loc.x, before.loc.y + 30).into(),
+            (before.size.w, before.size.h - 30).into()
+        ))
+    );
+
+    fixture.client(client).destroy_layer(layer);
+    fixture.settle();
+
+    assert_eq!(fixture.window_rect("window"), Some(before));
+}
+
+#[test]
+fn exclusive_keyboard_interactivity_takes_focus() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+    assert_eq!(fixture.focused(), Some(fixture.window("window")));
+
+    let layer = fixture.create_layer(
+        client,
+        "launcher",
+        Anchor::empty(),
+        (200, 100),
+        0,
+        KeyboardInteractivity::Exclusive,
+    );
+
+    assert_eq!(fixture.focused(), None);
+
+    // The window can't take the keyboard back while the layer surface is there
+    fixture.pointer_move((500.0, 100.0).into());
+    fixture.click(common::BTN_LEFT);
+    assert_eq!(fixture.focused(), None);
+
+    fixture.client(client).destroy_layer(layer);
+    fixture.settle();
+
+    assert_eq!(fixture.focused(), Some(fixture.window("window")));
+}
+
+#[test]
+fn fullscreen_window_ignores_exclusive_zones() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "window");
+    fixture.create_layer(
+        client,
+        "panel",
+        Anchor::Top | Anchor::Left | Anchor::Right,
+        (0, 30),
+        30,
+        KeyboardInteractivity::None,
+    );
+
+    fixture
+        .client(client)
+        .toplevel(toplevel)
+        .xdg_toplevel
+        .set_fullscreen(None);
+    fixture.settle();
+
+    assert_eq!(
+        fixture.window_rect("window"),
+        Some(Rectangle::new((0, 0).into(), (1000, 800).into()))
+    );
+}
@@ -0,0 +1,45 @@
+use pocowm::layout::{Id, Layout, LayoutType};
+
+#[test]
+fn new_layout_has_empty_root() {
+    let layout = Layout::new();
+    assert_eq!(layout.root().id, Id::default());
+    assert_eq!(layout.root().parent, None);
+    assert!(layout.root().children.is_empty());
+    assert_eq!(layout.root().layout_type, LayoutType::Horizontal);
+}
+
+#[test]
+fn sublayouts_are_nested() {
+    let mut layout = Layout::new();
+    let root = layout.root().id;
+    let outer = layout.insert_sublayout(root, LayoutType::Vertical).unwrap();
+    let inner = layout.insert_sublayout(outer, LayoutType::Tabbed).unwrap();
+
+    assert_eq!(layout.root().children, vec![outer]);
+    assert_eq!(layout.get_parent(inner), Some(outer));
+    assert_eq!(
+        layout.get_sublayout(outer).map(|sl| sl.children.clone()),
+        Some(vec![inner])
+    );
+    assert_eq!(
+        layout.get_sublayout(inner).map(|sl| sl.layout_type),
+        Some(LayoutType::Tabbed)
+    );
+}
+
+#[test]
+fn removing_last_child_removes_empty_parent() {
+    let mut layout = Layout::new();
+    let root = layout.root().id;
+    let outer = layout.insert_sublayout(root, LayoutType::Vertical).unwrap();
+    let inner = layout
+        .insert_sublayout(outer, LayoutType::Horizontal)
+        .unwrap();
+
+    assert!(layout.remove_element(inner).is_some());
+
+    assert!(layout.get_element(inner).is_none());
+    assert!(layout.get_element(outer).is_none());
+    assert!(layout.root().children.is_empty());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::keys::*;
+use common::Fixture;
+use pocowm::action::{Action, Direction};
+use pocowm::config::Config;
+use smithay::utils::Rectangle;
+
+/// Two 1000x800 outputs side by side, with a window on each
+fn fixture_with_two_outputs() -> Fixture {
+    let mut fixture = Fixture::new();
+    fixture.add_output((1000, 0).into());
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.pointer_move((1500.0, 400.0).into());
+    fixture.create_toplevel(client, "right");
+    fixture
+}
+
+#[test]
+fn windows_open_on_the_output_under_the_cursor() {
+    let fixture = fixture_with_two_outputs();
+
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((1020, 20).into(), (960, 760).into()))
+    );
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+    assert_eq!(fixture.server.workspaces().names(), vec!["1", "2"]);
+}
+
+#[test]
+fn focus_crosses_outputs() {
+    let mut fixture = fixture_with_two_outputs();
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_L]);
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+}
+
+#[test]
+fn window_moves_to_next_output() {
+    let mut fixture = fixture_with_two_outputs();
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+
+    fixture
+        .server
+        .run_action(Action::MoveToOutput(Direction::Right));
+    fixture.settle();
+
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((1020, 20).into(), (470, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((1510, 20).into(), (470, 760).into()))
+    );
+}
+
+#[test]
+fn output_config_is_applied_at_runtime() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+
+    let config: Config = toml::from_str(
+        r#"
+        [[outputs]]
+        name = "headless"
+        scale = 2
+        position = { x = 100, y = 0 }
+        mode = "800x600"
+        "#,
+    )
+    .expect("Invalid config");
+    fixture.server.apply_config(config);
+    fixture.settle();
+
+    assert_eq!(
+        fixture.server.renderer().output_geometry(&fixture.output),
+        Some(Rectangle::new((100, 0).into(), (400, 300).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("window"),
+        Some(Rectangle::new((120, 20).into(), (360, 260).into()))
+    );
+}
+
+#[test]
+fn fractional_scale_is_sent_to_windows() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+    assert_eq!(
+        fixture.client(client).toplevel(0).preferred_scale,
+        Some(120)
+    );
+
+    let config: Config = toml::from_str(
+        r#"
+        [[outputs]]
+        name = "headless"
+        scale = 1.5
+        "#,
+    )
+    .expect("Invalid config");
+    fixture.server.apply_config(config);
+    fixture.settle();
+
+    assert_eq!(
+        fixture.client(client).toplevel(0).preferred_scale,
+        Some(180)
+    );
+}
+
+#[test]
+fn disabled_output_hides_its_workspace() {
+    let mut fixture = fixture_with_two_outputs();
+    let right = fixture
+        .server
+        .renderer()
+        .outputs()
+        .find(|output| output.name() == "headless-2")
+        .cloned()
+        .expect("No second output");
+
+    fixture.server.disable_output(&right);
+    fixture.settle();
+
+    assert_eq!(fixture.window_rect("right"), None);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    assert_eq!(fixture.server.workspaces().names(), vec!["1", "2"]);
+
+    fixture.server.enable_output(&right);
+    fixture.settle();
+
+    assert_eq!(
+        fixture.server.renderer().output_geometry(&right),
+        Some(Rectangle::new((1000, 0).into(), (1000, 800).into()))
+    );
+    assert_eq!(fixture.server.workspaces().names(), vec!["1", "2"]);
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((1020, 20).into(), (960, 760).into()))
+    );
+}
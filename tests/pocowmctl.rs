@@ -0,0 +1,74 @@
+mod common;
+
+use common::Fixture;
+use pocowm::window::WindowState;
+use std::process::{Command, Output, Stdio};
+
+const MAX_ITERATIONS: usize = 1000;
+
+/// Runs pocowmctl against the fixture, dispatching the server until it exits
+fn pocowmctl(fixture: &mut Fixture, args: &[&str]) -> Output {
+    let socket = fixture.server.ipc_socket_path().expect("No IPC socket");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pocowmctl"))
+        .arg("--socket")
+        .arg(socket)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to spawn pocowmctl");
+    for _ in 0..MAX_ITERATIONS {
+        fixture.dispatch();
+        if child
+            .try_wait()
+            .expect("Failed to wait pocowmctl")
+            .is_some()
+        {
+            return child.wait_with_output().expect("Failed to read output");
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    let _ = child.kill();
+    panic!("pocowmctl timed out");
+}
+
+#[test]
+fn runs_commands() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+
+    let output = pocowmctl(&mut fixture, &["floating", "toggle"]);
+    fixture.settle();
+
+    assert!(output.status.success());
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::FLOATING));
+}
+
+#[test]
+fn prints_windows() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+
+    let output = pocowmctl(&mut fixture, &["windows"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "window 1 \"app\" (app) 20,20 960x760 *\n"
+    );
+}
+
+#[test]
+fn reports_errors() {
+    let mut fixture = Fixture::new();
+
+    let output = pocowmctl(&mut fixture, &["dance"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown command: dance"));
+}
@@ -0,0 +1,17 @@
+use pocowm::utils::text::{ellipsize, font};
+
+#[test]
+fn long_titles_are_ellipsized() {
+    let Some(font) = font("sans-serif") else {
+        eprintln!("No sans-serif font installed, skipping");
+        return;
+    };
+    let title = "A window title that is much too long for its title bar";
+
+    assert_eq!(ellipsize(&font, title, 13.0, 10_000.0), title);
+
+    let short = ellipsize(&font, title, 13.0, 150.0);
+    assert!(short.ends_with('…'));
+    assert!(title.starts_with(short.trim_end_matches('…')));
+    assert_eq!(ellipsize(&font, title, 13.0, 0.0), "");
+}
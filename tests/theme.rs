@@ -0,0 +1,81 @@
+mod common;
+
+use common::Fixture;
+use pocowm::action::Action;
+use pocowm::config::{Color, Config};
+
+fn themed_config() -> Config {
+    toml::from_str(
+        r##"
+        [theme.unfocused]
+        border = "#000000"
+
+        [themes.light]
+        border_width = 2
+        focused = { border = "#ffffff" }
+        "##,
+    )
+    .expect("Invalid config")
+}
+
+#[test]
+fn unfocused_windows_get_the_unfocused_border() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "first");
+    fixture.create_toplevel(client, "second");
+    assert!(!fixture.window("first").has_borders());
+
+    fixture.server.apply_config(themed_config());
+    fixture.settle();
+
+    let first = fixture.window("first");
+    assert!(first.has_borders());
+    assert_eq!(first.colors().border.0, [0.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn theme_is_switched_at_runtime() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+    fixture.server.apply_config(themed_config());
+
+    fixture.server.run_action(Action::Theme("light".into()));
+    fixture.settle();
+
+    let window = fixture.window("window");
+    assert_eq!(window.colors().border.0, [1.0, 1.0, 1.0, 1.0]);
+
+    // Unknown themes are ignored
+    fixture.server.run_action(Action::Theme("missing".into()));
+    fixture.server.run_action(Action::Theme("default".into()));
+    fixture.settle();
+
+    assert_eq!(
+        fixture.window("window").colors().border.0,
+        [0.0, 0.0, 1.0, 1.0]
+    );
+}
+
+#[test]
+fn deprecated_sections_are_read_into_the_theme() {
+    let config = Config::parse(
+        r##"
+        [borders]
+        size = 4
+        color = "#ff0000"
+
+        [decorations]
+        inactive_title = "#00ff00"
+        font_size = 10.0
+        "##,
+    )
+    .expect("Invalid config");
+
+    assert_eq!(config.theme.border_width, 4);
+    assert_eq!(config.theme.focused.border, Color([1.0, 0.0, 0.0, 1.0]));
+    assert_eq!(config.theme.unfocused.title, Color([0.0, 1.0, 0.0, 1.0]));
+    assert_eq!(config.theme.font_size, 10.0);
+    assert_eq!(config.borders, None);
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use common::keys::*;
+use common::{Fixture, BTN_LEFT, BTN_RIGHT};
+use pocowm::action::Action;
+use pocowm::layout::LayoutType;
+use smithay::backend::input::{ButtonState, KeyState};
+use smithay::backend::renderer::element::Element as _;
+use smithay::utils::Rectangle;
+
+#[test]
+fn tabbed_container_shows_last_focused_child() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Tabbed));
+    fixture.settle();
+
+    assert_eq!(fixture.window_rect("left"), None);
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((20, 44).into(), (960, 736).into()))
+    );
+}
+
+#[test]
+fn focus_keys_switch_tabs() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Tabbed));
+    fixture.settle();
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 44).into(), (960, 736).into()))
+    );
+    assert_eq!(fixture.window_rect("right"), None);
+}
+
+#[test]
+fn clicking_a_tab_switches_to_it() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Tabbed));
+    fixture.settle();
+
+    fixture.pointer_move((100.0, 30.0).into());
+    fixture.click(BTN_LEFT);
+
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    assert!(fixture.window_rect("left").is_some());
+    assert_eq!(fixture.window_rect("right"), None);
+}
+
+#[test]
+fn clicking_a_maximized_window_over_the_tab_bar_keeps_its_focus() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Tabbed));
+    fixture.key_combo(&[KEY_LEFTALT, KEY_M]);
+
+    fixture.pointer_move((100.0, 30.0).into());
+    fixture.click(BTN_LEFT);
+
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((0, 0).into(), (1000, 800).into()))
+    );
+}
+
+#[test]
+fn tab_buffers_are_kept_across_renders() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Tabbed));
+    fixture.settle();
+    let tab_ids = |fixture: &Fixture| {
+        let renderer = fixture.server.renderer();
+        let elements = renderer.tab_elements(&fixture.output);
+        elements.iter().map(|e| e.id().clone()).collect::<Vec<_>>()
+    };
+    let ids = tab_ids(&fixture);
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+
+    assert_eq!(ids.len(), 2);
+    assert_eq!(tab_ids(&fixture), ids);
+}
+
+#[test]
+fn stacked_container_shows_title_rows() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "top");
+    fixture.create_toplevel(client, "bottom");
+
+    fixture
+        .server
+        .run_action(Action::Layout(LayoutType::Stacked));
+    fixture.settle();
+
+    assert_eq!(fixture.window_rect("top"), None);
+    assert_eq!(
+        fixture.window_rect("bottom"),
+        Some(Rectangle::new((20, 68).into(), (960, 712).into()))
+    );
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_K]);
+    assert_eq!(fixture.focused(), Some(fixture.window("top")));
+    assert_eq!(fixture.window_rect("bottom"), None);
+
+    fixture.pointer_move((500.0, 50.0).into());
+    fixture.click(BTN_LEFT);
+    assert_eq!(fixture.focused(), Some(fixture.window("bottom")));
+    assert!(fixture.window_rect("bottom").is_some());
+}
+
+#[test]
+fn grow_gives_focused_window_a_larger_share() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.server.run_action(Action::Resize(10));
+    fixture.settle();
+
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (376, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((416, 20).into(), (564, 760).into()))
+    );
+}
+
+#[test]
+fn resize_keys_change_and_reset_split() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_MINUS]);
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((604, 20).into(), (376, 760).into()))
+    );
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_0]);
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (470, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((510, 20).into(), (470, 760).into()))
+    );
+}
+
+#[test]
+fn dragging_the_gap_resizes_siblings() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.pointer_move((500.0, 400.0).into());
+    fixture.button(BTN_LEFT, ButtonState::Pressed);
+    fixture.pointer_move((600.0, 400.0).into());
+
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (570, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((610, 20).into(), (370, 760).into()))
+    );
+
+    fixture.button(BTN_LEFT, ButtonState::Released);
+    fixture.pointer_move((700.0, 400.0).into());
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (570, 760).into()))
+    );
+}
+
+#[test]
+fn modifier_drag_resizes_tiled_window() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.pointer_move((400.0, 600.0).into());
+    fixture.key(KEY_LEFTALT, KeyState::Pressed);
+    fixture.button(BTN_RIGHT, ButtonState::Pressed);
+    fixture.pointer_move((300.0, 600.0).into());
+    fixture.button(BTN_RIGHT, ButtonState::Released);
+    fixture.key(KEY_LEFTALT, KeyState::Released);
+
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (370, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((410, 20).into(), (570, 760).into()))
+    );
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::keys::*;
+use common::Fixture;
+use pocowm::action::Action;
+use pocowm::window::WindowState;
+use pocowm::workspace::WorkspaceTarget;
+use smithay::utils::Rectangle;
+
+#[test]
+fn switching_workspace_hides_windows_and_restores_focus() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "first");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_2]);
+    assert_eq!(fixture.window_rect("first"), None);
+    assert_eq!(fixture.focused(), None);
+
+    fixture.create_toplevel(client, "second");
+    assert_eq!(
+        fixture.window_rect("second"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_1]);
+    assert_eq!(fixture.window_rect("second"), None);
+    assert!(fixture.window_rect("first").is_some());
+    assert_eq!(fixture.focused(), Some(fixture.window("first")));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_2]);
+    assert_eq!(fixture.focused(), Some(fixture.window("second")));
+}
+
+#[test]
+fn focused_window_moves_to_workspace() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "stays");
+    fixture.create_toplevel(client, "moves");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_2]);
+    assert_eq!(fixture.window_rect("moves"), None);
+    assert_eq!(
+        fixture.window_rect("stays"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+    assert_eq!(fixture.focused(), Some(fixture.window("stays")));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_2]);
+    assert_eq!(
+        fixture.window_rect("moves"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+    assert_eq!(fixture.focused(), Some(fixture.window("moves")));
+}
+
+#[test]
+fn empty_workspaces_are_dropped_when_left() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "window");
+
+    fixture
+        .server
+        .run_action(Action::Workspace(WorkspaceTarget::Name("web".into())));
+    assert_eq!(fixture.server.workspaces().names(), vec!["1", "web"]);
+
+    fixture
+        .server
+        .run_action(Action::Workspace(WorkspaceTarget::Prev));
+    fixture.settle();
+    assert_eq!(fixture.server.workspaces().names(), vec!["1"]);
+    assert_eq!(fixture.focused(), Some(fixture.window("window")));
+}
+
+#[test]
+fn windows_of_hidden_workspaces_handle_their_requests() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "hidden");
+    fixture.key_combo(&[KEY_LEFTALT, KEY_2]);
+
+    fixture
+        .client(client)
+        .toplevel(toplevel)
+        .xdg_toplevel
+        .set_maximized();
+    fixture.settle();
+
+    assert!(fixture
+        .window("hidden")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+    assert_eq!(fixture.window_rect("hidden"), None);
+}
@@ -0,0 +1,289 @@
+mod common;
+
+use common::keys::*;
+use common::Fixture;
+use pocowm::window::WindowState;
+use smithay::utils::Rectangle;
+use wayland_protocols::xdg::shell::client::xdg_toplevel;
+
+#[test]
+fn first_toplevel_fills_the_output() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "first");
+
+    assert_eq!(
+        fixture.window_rect("first"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+    assert_eq!(fixture.focused(), Some(fixture.window("first")));
+}
+
+#[test]
+fn toplevels_are_split_horizontally() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (470, 760).into()))
+    );
+    assert_eq!(
+        fixture.window_rect("right"),
+        Some(Rectangle::new((510, 20).into(), (470, 760).into()))
+    );
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+}
+
+#[test]
+fn destroying_focused_toplevel_focuses_sibling() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    let right = fixture.create_toplevel(client, "right");
+
+    fixture.client(client).destroy_toplevel(right);
+    fixture.settle();
+
+    assert_eq!(fixture.server.layout().iter_windows().count(), 1);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    assert_eq!(
+        fixture.window_rect("left"),
+        Some(Rectangle::new((20, 20).into(), (960, 760).into()))
+    );
+}
+
+#[test]
+fn maximize_request_sets_state() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "app");
+
+    fixture
+        .client(client)
+        .toplevel(toplevel)
+        .xdg_toplevel
+        .set_maximized();
+    fixture.settle();
+
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+    assert_eq!(
+        fixture.window_rect("app"),
+        Some(Rectangle::new((0, 0).into(), (1000, 800).into()))
+    );
+}
+
+#[test]
+fn focus_follows_keyboard_navigation() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_H]);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_L]);
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+}
+
+#[test]
+fn toggle_floating_uses_floating_rect() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_F]);
+
+    let window = fixture.window("app");
+    assert!(window.state().contains(WindowState::FLOATING));
+    assert_eq!(
+        fixture.window_rect("app"),
+        Some(Rectangle::new((250, 200).into(), (500, 400).into()))
+    );
+}
+
+#[test]
+fn clicking_a_window_focuses_it() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+
+    fixture.pointer_move((100.0, 100.0).into());
+    fixture.click(common::BTN_LEFT);
+
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+}
+
+#[test]
+fn close_binding_sends_close() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "app");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_Q]);
+
+    assert!(fixture.client(client).toplevel(toplevel).closed);
+}
+
+#[test]
+fn maximize_binding_toggles_maximized() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_M]);
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_M]);
+    assert!(!fixture
+        .window("app")
+        .state()
+        .contains(WindowState::MAXIMIZED));
+}
+
+#[test]
+fn fullscreen_request_covers_the_output() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    let toplevel = fixture.create_toplevel(client, "app");
+    let tiled = fixture.window_rect("app");
+
+    fixture
+        .client(client)
+        .toplevel(toplevel)
+        .xdg_toplevel
+        .set_fullscreen(None);
+    fixture.settle();
+
+    assert!(fixture
+        .window("app")
+        .state()
+        .contains(WindowState::FULLSCREEN));
+    assert!(fixture
+        .client(client)
+        .toplevel(toplevel)
+        .states
+        .contains(&xdg_toplevel::State::Fullscreen));
+    assert_eq!(
+        fixture.window_rect("app"),
+        Some(Rectangle::new((0, 0).into(), (1000, 800).into()))
+    );
+
+    fixture
+        .client(client)
+        .toplevel(toplevel)
+        .xdg_toplevel
+        .unset_fullscreen();
+    fixture.settle();
+
+    assert!(fixture.window("app").state().is_empty());
+    assert_eq!(fixture.window_rect("app"), tiled);
+}
+
+#[test]
+fn fullscreen_binding_restores_the_previous_state() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "app");
+    fixture.key_combo(&[KEY_LEFTALT, KEY_M]);
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_F]);
+    assert_eq!(
+        fixture.window("app").state().clone(),
+        WindowState::MAXIMIZED | WindowState::FULLSCREEN
+    );
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_F]);
+    assert_eq!(
+        fixture.window("app").state().clone(),
+        WindowState::MAXIMIZED
+    );
+}
+
+#[test]
+fn destroying_focused_toplevel_skips_minimized_siblings() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "first");
+    let second = fixture.create_toplevel(client, "second");
+    let third = fixture.create_toplevel(client, "third");
+
+    fixture
+        .client(client)
+        .toplevel(second)
+        .xdg_toplevel
+        .set_minimized();
+    fixture.settle();
+    assert_eq!(fixture.focused(), Some(fixture.window("third")));
+
+    fixture.client(client).destroy_toplevel(third);
+    fixture.settle();
+
+    assert_eq!(fixture.focused(), Some(fixture.window("first")));
+}
+
+#[test]
+fn unminimize_binding_restores_the_last_minimized_window() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "left");
+    fixture.create_toplevel(client, "right");
+    let tiled = fixture.window_rect("right");
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_M]);
+    assert_eq!(fixture.window_rect("right"), None);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_M]);
+    assert_eq!(fixture.focused(), None);
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_U]);
+    assert_eq!(fixture.focused(), Some(fixture.window("left")));
+    assert!(fixture
+        .window("right")
+        .state()
+        .contains(WindowState::MINIMIZED));
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_U]);
+    assert_eq!(fixture.focused(), Some(fixture.window("right")));
+    assert!(fixture.window("right").state().is_empty());
+    assert_eq!(fixture.window_rect("right"), tiled);
+}
+
+#[test]
+fn picker_restores_the_selected_window() {
+    let mut fixture = Fixture::new();
+    let client = fixture.add_client();
+    fixture.create_toplevel(client, "first");
+    fixture.create_toplevel(client, "second");
+    fixture.create_toplevel(client, "third");
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_M]);
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_M]);
+
+    fixture.key_combo(&[KEY_LEFTALT, KEY_LEFTSHIFT, KEY_U]);
+    let picker = fixture.server.renderer().picker.as_ref();
+    assert_eq!(
+        picker.map(|picker| picker.windows.clone()),
+        Some(vec![fixture.window("second"), fixture.window("third")])
+    );
+
+    // Keys go to the picker instead of the focused window
+    fixture.key_combo(&[KEY_L]);
+    fixture.key_combo(&[KEY_ENTER]);
+
+    assert!(fixture.server.renderer().picker.is_none());
+    assert_eq!(fixture.focused(), Some(fixture.window("third")));
+    assert!(fixture
+        .window("second")
+        .state()
+        .contains(WindowState::MINIMIZED));
+}